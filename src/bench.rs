@@ -0,0 +1,74 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Counts heap allocations so that benchmarks can report them next to the timings. It is only
+/// the global allocator with the `count-allocations` feature, which slows down every binary.
+pub struct CountingAllocator;
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+pub struct Measurement {
+    pub elapsed: Duration,
+    pub allocations: usize,
+    pub allocated_bytes: usize,
+    pub checksum: usize,
+}
+
+impl Measurement {
+    /// One line report, labelled with `name`.
+    pub fn report(&self, name: &str) -> String {
+        if !cfg!(feature = "count-allocations") {
+            return format!(
+                "{name:>8}: {:>10.2?}, allocations not counted without the count-allocations \
+                 feature, checksum {}",
+                self.elapsed, self.checksum
+            );
+        }
+        format!(
+            "{name:>8}: {:>10.2?}, {:>9} allocations, {:>8.1} MiB allocated, checksum {}",
+            self.elapsed,
+            self.allocations,
+            self.allocated_bytes as f64 / (1024.0 * 1024.0),
+            self.checksum
+        )
+    }
+}
+
+/// Times `run` and counts what it allocates. Allocations are only counted with the
+/// `count-allocations` feature.
+pub fn measure(run: impl FnOnce() -> usize) -> Measurement {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED_BYTES.store(0, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let checksum = black_box(run());
+    let elapsed = start.elapsed();
+
+    Measurement {
+        elapsed,
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        checksum,
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use advent_of_code_2023::input;
+
 const SPELLED_DIGITS: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
@@ -69,7 +71,8 @@ fn process_input(input: &str) -> usize {
 }
 
 fn main() {
-    let input = include_str!("../../inputs/real/day1.txt");
+    let input = input::from_args("day1", include_str!("../../inputs/real/day1.txt"));
+    let input: &str = &input;
     // let input = "eight";
 
     let answer: usize = process_input(input);
@@ -1,60 +1,169 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use advent_of_code_2023::{
+    input,
+    parsing::{number, numbered_header, parse_all},
+};
 use nom::{
-    branch::alt,
     bytes::complete::tag,
-    character::complete::{digit1, newline},
+    character::complete::{alpha1, newline, space1},
     combinator::map,
     multi::{separated_list0, separated_list1},
-    sequence::{delimited, separated_pair, tuple},
+    sequence::{separated_pair, tuple},
     IResult,
 };
 
-enum Cube {
-    Red(usize),
-    Green(usize),
-    Blue(usize),
+struct Cube<'a> {
+    color: &'a str,
+    quantity: usize,
+}
+
+/// How to treat a color showing up more than once in a single draw, e.g. "3 red, 4 red".
+#[derive(Clone, Copy)]
+enum DuplicateColors {
+    Sum,
+    Reject,
+}
+
+#[derive(Debug)]
+struct DuplicateColor {
+    game_id: usize,
+    /// Position of the draw within the game, counting from 1 like game ids.
+    draw: usize,
+    color: String,
+}
+
+impl std::fmt::Display for DuplicateColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "game {}, draw {}: {} is listed more than once",
+            self.game_id, self.draw, self.color
+        )
+    }
 }
 
 #[derive(Debug)]
 struct Bag(CubesSubset);
 
-#[derive(Debug, PartialEq)]
-struct CubesSubset {
-    red: Option<usize>,
-    green: Option<usize>,
-    blue: Option<usize>,
+impl Bag {
+    fn power(&self) -> Option<usize> {
+        self.0.power()
+    }
 }
 
+impl Default for Bag {
+    fn default() -> Self {
+        Bag(CubesSubset(BTreeMap::from([
+            ("red".to_owned(), 12),
+            ("green".to_owned(), 13),
+            ("blue".to_owned(), 14),
+        ])))
+    }
+}
+
+/// Multiset of cubes keyed by color name. Colors missing from the map count as zero cubes.
+#[derive(Debug, Default, Clone)]
+struct CubesSubset(BTreeMap<String, usize>);
+
 impl CubesSubset {
+    /// Collects the cubes of one draw, returning the first repeated color when duplicates are
+    /// rejected.
+    fn from_cubes(cubes: Vec<Cube>, duplicates: DuplicateColors) -> Result<Self, String> {
+        let mut cubes_subset = CubesSubset::default();
+
+        for Cube { color, quantity } in cubes {
+            match (cubes_subset.0.get_mut(color), duplicates) {
+                (None, _) => {
+                    cubes_subset.0.insert(color.to_owned(), quantity);
+                }
+                (Some(total), DuplicateColors::Sum) => *total += quantity,
+                (Some(_), DuplicateColors::Reject) => return Err(color.to_owned()),
+            }
+        }
+
+        Ok(cubes_subset)
+    }
+
+    fn count(&self, color: &str) -> usize {
+        self.0.get(color).copied().unwrap_or_default()
+    }
+
+    fn colors(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
     fn is_within_range(&self, other: &CubesSubset) -> bool {
-        let (r1, g1, b1) = match self {
-            CubesSubset { red, green, blue } => (
-                red.unwrap_or_default(),
-                green.unwrap_or_default(),
-                blue.unwrap_or_default(),
-            ),
-        };
-        let (r2, g2, b2) = match other {
-            CubesSubset { red, green, blue } => (
-                red.unwrap_or_default(),
-                green.unwrap_or_default(),
-                blue.unwrap_or_default(),
-            ),
-        };
-        r1 <= r2 && g1 <= g2 && b1 <= b2
+        self.0
+            .iter()
+            .all(|(color, quantity)| *quantity <= other.count(color))
+    }
+
+    /// Number of cubes, or `None` when there are too many to count.
+    fn total(&self) -> Option<usize> {
+        self.0
+            .values()
+            .try_fold(0usize, |total, quantity| total.checked_add(*quantity))
+    }
+
+    /// Product of the quantities, or `None` when it is too large to count.
+    fn power(&self) -> Option<usize> {
+        self.0
+            .values()
+            .try_fold(1usize, |power, quantity| power.checked_mul(*quantity))
+    }
+
+    /// Least upper bound: the larger quantity of every color.
+    fn join(&self, other: &CubesSubset) -> CubesSubset {
+        let mut joined = self.clone();
+        for (color, quantity) in &other.0 {
+            let joined_quantity = joined.0.entry(color.clone()).or_default();
+            *joined_quantity = (*joined_quantity).max(*quantity);
+        }
+        joined
+    }
+
+    /// Greatest lower bound: the smaller quantity of every color.
+    fn meet(&self, other: &CubesSubset) -> CubesSubset {
+        CubesSubset(
+            self.0
+                .iter()
+                .filter(|(color, _)| other.0.contains_key(*color))
+                .map(|(color, quantity)| (color.clone(), (*quantity).min(other.count(color))))
+                .collect(),
+        )
     }
 }
 
+/// Written the way draws and `--bag` are, e.g. "14 blue, 13 green, 12 red".
+impl std::fmt::Display for CubesSubset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubes = self
+            .0
+            .iter()
+            .map(|(color, quantity)| format!("{quantity} {color}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{cubes}")
+    }
+}
+
+impl PartialEq for CubesSubset {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_within_range(other) && other.is_within_range(self)
+    }
+}
+
+/// Component-wise dominance: a subset is less than another when it fits within it. Subsets where
+/// each has more of some color than the other are incomparable.
 impl PartialOrd for CubesSubset {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.red.partial_cmp(&other.red) {
-            Some(core::cmp::Ordering::Equal | core::cmp::Ordering::Less) => {}
-            ord => return ord,
-        }
-        match self.green.partial_cmp(&other.green) {
-            Some(core::cmp::Ordering::Equal | core::cmp::Ordering::Less) => {}
-            ord => return ord,
+        match (self.is_within_range(other), other.is_within_range(self)) {
+            (true, true) => Some(std::cmp::Ordering::Equal),
+            (true, false) => Some(std::cmp::Ordering::Less),
+            (false, true) => Some(std::cmp::Ordering::Greater),
+            (false, false) => None,
         }
-        self.blue.partial_cmp(&other.blue)
     }
 }
 
@@ -64,91 +173,258 @@ struct Game {
     cubes_subsets: Vec<CubesSubset>,
 }
 
-fn game_id(input: &str) -> IResult<&str, usize> {
-    let parse_game_id = delimited(tag("Game "), digit1, tag(": "));
+/// A game as written in the input, before the cubes of each draw are combined.
+struct RawGame<'a> {
+    id: usize,
+    draws: Vec<Vec<Cube<'a>>>,
+}
 
-    let game_id = |s: &str| s.parse::<usize>().expect("could not parse game id:: {s}");
+impl RawGame<'_> {
+    fn resolve(self, duplicates: DuplicateColors) -> Result<Game, DuplicateColor> {
+        let cubes_subsets = self
+            .draws
+            .into_iter()
+            .enumerate()
+            .map(|(draw_index, draw)| {
+                CubesSubset::from_cubes(draw, duplicates).map_err(|color| DuplicateColor {
+                    game_id: self.id,
+                    draw: draw_index + 1,
+                    color,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-    map(parse_game_id, game_id)(input)
+        Ok(Game {
+            id: self.id,
+            cubes_subsets,
+        })
+    }
 }
 
-fn cubes(input: &str) -> IResult<&str, Cube> {
-    let parse_cube = separated_pair(
-        digit1,
-        nom::character::complete::space1,
-        alt((tag("red"), tag("green"), tag("blue"))),
-    );
+impl Game {
+    fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.cubes_subsets
+            .iter()
+            .all(|cubes_subset| *cubes_subset <= bag.0)
+    }
 
-    let cube = |(quantity, color): (&str, &str)| -> Cube {
-        let quantity = quantity
-            .parse::<usize>()
-            .expect("could not parse cube quantity: {quantity}");
-        match color {
-            "red" => Cube::Red(quantity),
-            "green" => Cube::Green(quantity),
-            "blue" => Cube::Blue(quantity),
-            _ => panic!("unknown cube color: {color}"),
-        }
-    };
+    /// Fewest cubes of every color in `palette` that make the game possible; a color of the
+    /// palette that never shows up in the game stays at zero.
+    fn minimum_bag(&self, palette: &BTreeSet<&str>) -> Bag {
+        let zero = CubesSubset(palette.iter().map(|color| (color.to_string(), 0)).collect());
 
-    map(parse_cube, cube)(input)
+        Bag(self
+            .cubes_subsets
+            .iter()
+            .fold(zero, |min_possible, cubes_subset| {
+                min_possible.join(cubes_subset)
+            }))
+    }
+
+    /// Average number of cubes of every color in `palette` per draw.
+    fn mean_draw(&self, palette: &BTreeSet<&str>) -> BTreeMap<String, f64> {
+        let draws = self.cubes_subsets.len().max(1) as f64;
+
+        palette
+            .iter()
+            .map(|color| {
+                let total = self
+                    .cubes_subsets
+                    .iter()
+                    .map(|cubes_subset| cubes_subset.count(color))
+                    .sum::<usize>();
+                (color.to_string(), total as f64 / draws)
+            })
+            .collect()
+    }
+
+    /// Number of bags holding at most `limit` cubes of each color that make the game possible,
+    /// or `None` when there are too many to count.
+    fn possible_bags_within(&self, limit: &Bag) -> Option<usize> {
+        let palette = limit.0.colors().collect::<BTreeSet<_>>();
+        let Bag(CubesSubset(min_possible)) = self.minimum_bag(&palette);
+
+        min_possible.iter().try_fold(1usize, |bags, (color, min)| {
+            let choices = limit.0.count(color).checked_add(1)?.saturating_sub(*min);
+            bags.checked_mul(choices)
+        })
+    }
+
+    /// Probability of observing every draw of the game when each one takes its cubes uniformly
+    /// at random, without replacement, from `bag`, and puts them back afterwards. `None` when
+    /// the bag holds too many cubes to count.
+    fn likelihood(&self, bag: &Bag) -> Option<f64> {
+        let bag_total = bag.0.total()?;
+
+        Some(
+            self.cubes_subsets
+                .iter()
+                .map(|cubes_subset| {
+                    if !cubes_subset.is_within_range(&bag.0) {
+                        return 0.0;
+                    }
+                    // within the bag, so no more cubes than it holds
+                    let drawn_total = cubes_subset.total().unwrap_or(bag_total);
+                    let ln_favourable = cubes_subset
+                        .0
+                        .iter()
+                        .map(|(color, quantity)| ln_binomial(bag.0.count(color), *quantity))
+                        .sum::<f64>();
+
+                    (ln_favourable - ln_binomial(bag_total, drawn_total)).exp()
+                })
+                .product(),
+        )
+    }
+}
+
+/// Natural logarithm of the binomial coefficient `n choose k`, for `k <= n`.
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
 }
 
-fn cubes_subset(input: &str) -> IResult<&str, CubesSubset> {
-    let parse_cubes_subset = separated_list0(tag(", "), cubes);
+/// Every color drawn in any of the games.
+fn palette(games: &[Game]) -> BTreeSet<&str> {
+    games
+        .iter()
+        .flat_map(|game| &game.cubes_subsets)
+        .flat_map(CubesSubset::colors)
+        .collect()
+}
 
-    let cubes_subset = |cubes: Vec<Cube>| {
-        let mut cubes_subset = CubesSubset {
-            red: None,
-            green: None,
-            blue: None,
-        };
+fn game_id(input: &str) -> IResult<&str, usize> {
+    numbered_header(tag("Game"))(input)
+}
 
-        for cube in cubes {
-            match cube {
-                Cube::Red(q) => cubes_subset.red = Some(q),
-                Cube::Green(q) => cubes_subset.green = Some(q),
-                Cube::Blue(q) => cubes_subset.blue = Some(q),
-            }
-        }
+fn cubes<'a>(input: &'a str) -> IResult<&'a str, Cube<'a>> {
+    let parse_cube = separated_pair(number, space1, alpha1);
 
-        cubes_subset
-    };
+    let cube = |(quantity, color): (usize, &'a str)| -> Cube<'a> { Cube { color, quantity } };
+
+    map(parse_cube, cube)(input)
+}
 
-    map(parse_cubes_subset, cubes_subset)(input)
+fn draw<'a>(input: &'a str) -> IResult<&'a str, Vec<Cube<'a>>> {
+    separated_list0(tag(", "), cubes)(input)
 }
 
-fn game_subsets(input: &str) -> IResult<&str, Vec<CubesSubset>> {
-    separated_list0(tag("; "), cubes_subset)(input)
+fn game_draws<'a>(input: &'a str) -> IResult<&'a str, Vec<Vec<Cube<'a>>>> {
+    separated_list0(tag("; "), draw)(input)
 }
 
-fn game(input: &str) -> IResult<&str, Game> {
-    let parse_game = tuple((game_id, game_subsets));
+fn game<'a>(input: &'a str) -> IResult<&'a str, RawGame<'a>> {
+    let parse_game = tuple((game_id, game_draws));
 
-    let game = |(game_id, game_subsets): (usize, Vec<CubesSubset>)| Game {
-        id: game_id,
-        cubes_subsets: game_subsets,
-    };
+    let game = |(id, draws): (usize, Vec<Vec<Cube<'a>>>)| RawGame { id, draws };
 
     map(parse_game, game)(input)
 }
 
-fn parse_games(input: &str) -> IResult<&str, Vec<Game>> {
+fn parse_games(input: &str) -> IResult<&str, Vec<RawGame<'_>>> {
     separated_list1(newline, game)(input)
 }
 
+/// What to compute once the games are parsed.
+enum Query {
+    /// Part 1 and part 2 answers for the configured bag.
+    Answers,
+    /// Ids of the games that are possible with the configured bag.
+    PossibleGames,
+    /// The fewest cubes of each color that make the given game possible.
+    MinimumBag(usize),
+    /// Per-game draw statistics and the likelihood of the draws under the configured bag.
+    Stats,
+}
+
+struct Config {
+    bag: Bag,
+    duplicates: DuplicateColors,
+    query: Query,
+    input: Option<String>,
+}
+
+const USAGE: &str = "usage: day2 [--bag \"12 red, 13 green, 14 blue\"]... [--strict] \
+                     [--input <path>] [answers | possible | stats | min-bag <game id>]";
+
+fn bag(input: &str) -> Result<Bag, String> {
+    match draw(input) {
+        Ok(("", cubes)) => CubesSubset::from_cubes(cubes, DuplicateColors::Reject)
+            .map(Bag)
+            .map_err(|color| format!("bag lists {color} more than once")),
+        _ => Err(format!("could not parse bag: {input}")),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut bag_config: Option<Bag> = None;
+    let mut duplicates = DuplicateColors::Sum;
+    let mut query = Query::Answers;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bag" => {
+                let value = args.next().ok_or("--bag expects a value")?;
+                let value = bag(&value)?;
+                // a game fits every given bag exactly when it fits their meet
+                bag_config = Some(match bag_config {
+                    Some(Bag(previous)) => Bag(previous.meet(&value.0)),
+                    None => value,
+                });
+            }
+            "--strict" => duplicates = DuplicateColors::Reject,
+            "--input" => input = Some(args.next().ok_or("--input expects a path")?),
+            "answers" => query = Query::Answers,
+            "possible" => query = Query::PossibleGames,
+            "stats" => query = Query::Stats,
+            "min-bag" => {
+                let id = args.next().ok_or("min-bag expects a game id")?;
+                let id = id
+                    .parse::<usize>()
+                    .map_err(|_| format!("could not parse game id: {id}"))?;
+                query = Query::MinimumBag(id);
+            }
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+
+    Ok(Config {
+        bag: bag_config.unwrap_or_default(),
+        duplicates,
+        query,
+        input,
+    })
+}
+
 const DEBUG: bool = false;
 
 fn main() {
-    let actual_bag = Bag(CubesSubset {
-        red: Some(12),
-        green: Some(13),
-        blue: Some(14),
+    let config = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!("{USAGE}");
+        std::process::exit(2);
     });
 
-    let input = include_str!("../../inputs/real/day2.txt");
+    let input = input::load(
+        config.input.as_deref(),
+        include_str!("../../inputs/real/day2.txt"),
+    );
+    let input: &str = &input;
+
+    let raw_games = parse_all(input, parse_games).unwrap_or_else(|err| {
+        eprintln!("malformed game at {err}");
+        std::process::exit(1);
+    });
 
-    let (_, games) = parse_games(input).expect("could not parse games");
+    let games = raw_games
+        .into_iter()
+        .map(|raw_game| raw_game.resolve(config.duplicates))
+        .collect::<Result<Vec<Game>, _>>()
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1);
+        });
 
     if DEBUG {
         for game in &games {
@@ -156,14 +432,59 @@ fn main() {
         }
     }
 
+    match config.query {
+        Query::Answers => {}
+        Query::PossibleGames => {
+            for game in games
+                .iter()
+                .filter(|game| game.is_possible_with(&config.bag))
+            {
+                println!("{}", game.id);
+            }
+            return;
+        }
+        Query::MinimumBag(id) => {
+            let Some(game) = games.iter().find(|game| game.id == id) else {
+                eprintln!("no game with id {id}");
+                std::process::exit(1);
+            };
+            let min_possible_bag = game.minimum_bag(&palette(&games));
+            println!("{}", min_possible_bag.0);
+            match min_possible_bag.power() {
+                Some(power) => println!("power: {power}"),
+                None => println!("power: too large to count"),
+            }
+            return;
+        }
+        Query::Stats => {
+            let palette = palette(&games);
+            for game in &games {
+                let mean = game
+                    .mean_draw(&palette)
+                    .iter()
+                    .map(|(color, mean)| format!("{mean:.2} {color}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("game {}", game.id);
+                println!("  max: {}", game.minimum_bag(&palette).0);
+                println!("  mean: {mean}");
+                match game.possible_bags_within(&config.bag) {
+                    Some(bags) => println!("  possible bags: {bags}"),
+                    None => println!("  possible bags: too many to count"),
+                }
+                match game.likelihood(&config.bag) {
+                    Some(likelihood) => println!("  likelihood: {likelihood:.3e}"),
+                    None => println!("  likelihood: too many cubes in the bag to tell"),
+                }
+            }
+            return;
+        }
+    }
+
     let answer_p1 = games
         .iter()
         .filter_map(|game| {
-            if game
-                .cubes_subsets
-                .iter()
-                .all(|cube_subset| cube_subset.is_within_range(&actual_bag.0))
-            {
+            if game.is_possible_with(&config.bag) {
                 if DEBUG {
                     println!("{} is possible", game.id);
                 }
@@ -177,36 +498,144 @@ fn main() {
         })
         .sum::<usize>();
 
+    let palette = palette(&games);
     let answer_p2 = games
         .iter()
         .map(|game| {
-            let mut min_possible_bag = Bag(CubesSubset {
-                red: Some(0),
-                green: Some(0),
-                blue: Some(0),
-            });
-
-            for cubes_subset in &game.cubes_subsets {
-                let CubesSubset { red, green, blue } = *cubes_subset;
-
-                if red > min_possible_bag.0.red {
-                    min_possible_bag.0.red = red;
-                }
-                if green > min_possible_bag.0.green {
-                    min_possible_bag.0.green = green;
-                }
-                if blue > min_possible_bag.0.blue {
-                    min_possible_bag.0.blue = blue;
-                }
-            }
+            let min_possible_bag = game.minimum_bag(&palette);
             if DEBUG {
                 println!("game {}: min possible bag {:?}", game.id, min_possible_bag);
             }
-            min_possible_bag.0.red.unwrap_or(1)
-                * min_possible_bag.0.green.unwrap_or(1)
-                * min_possible_bag.0.blue.unwrap_or(1)
+            min_possible_bag.power()
         })
-        .sum::<usize>();
+        .try_fold(0usize, |total, power| total.checked_add(power?))
+        .unwrap_or_else(|| {
+            eprintln!("power too large to count");
+            std::process::exit(1);
+        });
     println!("{answer_p1}");
     println!("{answer_p2}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed_game(line: &str) -> Game {
+        let mut raw_games = parse_all(line, parse_games).unwrap();
+        raw_games
+            .remove(0)
+            .resolve(DuplicateColors::Reject)
+            .unwrap()
+    }
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!(
+            (found - expected).abs() < 1e-9,
+            "expected {expected}, found {found}"
+        );
+    }
+
+    #[test]
+    fn ln_binomial_matches_small_coefficients() {
+        assert_close(ln_binomial(5, 2), 10f64.ln());
+        assert_close(ln_binomial(6, 6), 0.0);
+        assert_close(ln_binomial(4, 0), 0.0);
+    }
+
+    #[test]
+    fn likelihood_is_hypergeometric_per_draw() {
+        let bag = bag("1 red, 2 blue").unwrap();
+
+        // C(1, 1) * C(2, 1) / C(3, 2)
+        let one_draw = parsed_game("Game 1: 1 red, 1 blue");
+        assert_close(one_draw.likelihood(&bag).unwrap(), 2.0 / 3.0);
+
+        // C(1, 1) / C(3, 1), then C(2, 2) / C(3, 2)
+        let two_draws = parsed_game("Game 1: 1 red; 2 blue");
+        assert_close(two_draws.likelihood(&bag).unwrap(), 1.0 / 9.0);
+
+        let too_many = parsed_game("Game 1: 2 red");
+        assert_close(too_many.likelihood(&bag).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn mean_draw_counts_missing_colors_as_zero() {
+        let game = parsed_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        let palette = BTreeSet::from(["blue", "green", "red", "yellow"]);
+        let mean = game.mean_draw(&palette);
+
+        assert_close(mean["blue"], 3.0);
+        assert_close(mean["green"], 4.0 / 3.0);
+        assert_close(mean["red"], 5.0 / 3.0);
+        assert_close(mean["yellow"], 0.0);
+    }
+
+    #[test]
+    fn possible_bags_within_the_default_bag() {
+        let game = parsed_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        // 4..=12 red, 2..=13 green and 6..=14 blue
+        assert_eq!(game.possible_bags_within(&Bag::default()), Some(9 * 12 * 9));
+
+        let impossible = parsed_game("Game 2: 20 red");
+        assert_eq!(impossible.possible_bags_within(&Bag::default()), Some(0));
+
+        let huge = bag("100000 red, 100000 green, 100000 blue, 100000 x").unwrap();
+        assert_eq!(game.possible_bags_within(&huge), None);
+    }
+
+    fn subset(cubes: &str) -> CubesSubset {
+        bag(cubes).unwrap().0
+    }
+
+    #[test]
+    fn missing_colors_count_as_zero() {
+        assert_eq!(subset("3 red"), subset("3 red, 0 blue"));
+        assert_ne!(subset("3 red"), subset("3 red, 1 blue"));
+        assert!(subset("3 red") < subset("3 red, 1 blue"));
+    }
+
+    #[test]
+    fn subsets_are_ordered_by_dominance() {
+        use std::cmp::Ordering;
+
+        let small = subset("1 red, 2 blue");
+        let large = subset("2 red, 2 blue, 1 green");
+        assert_eq!(small.partial_cmp(&large), Some(Ordering::Less));
+        assert_eq!(large.partial_cmp(&small), Some(Ordering::Greater));
+        assert_eq!(small.partial_cmp(&small.clone()), Some(Ordering::Equal));
+
+        let more_red = subset("3 red");
+        let more_blue = subset("3 blue");
+        assert_eq!(more_red.partial_cmp(&more_blue), None);
+        assert!(!(more_red <= more_blue) && !(more_blue <= more_red));
+    }
+
+    #[test]
+    fn join_and_meet_form_a_lattice() {
+        let subsets = [
+            subset("1 red, 2 blue"),
+            subset("3 red"),
+            subset("2 blue, 4 green"),
+            subset("0 red, 5 green"),
+        ];
+
+        for a in &subsets {
+            assert_eq!(a.join(a), *a);
+            assert_eq!(a.meet(a), *a);
+            for b in &subsets {
+                assert_eq!(a.join(b), b.join(a));
+                assert_eq!(a.meet(b), b.meet(a));
+                assert_eq!(a.join(&a.meet(b)), *a);
+                assert_eq!(a.meet(&a.join(b)), *a);
+                assert!(a.meet(b) <= *a && *a <= a.join(b));
+                for c in &subsets {
+                    assert_eq!(a.join(b).join(c), a.join(&b.join(c)));
+                    assert_eq!(a.meet(b).meet(c), a.meet(&b.meet(c)));
+                }
+            }
+        }
+        assert_eq!(subsets[0].join(&subsets[1]), subset("3 red, 2 blue"));
+        assert_eq!(subsets[0].meet(&subsets[2]), subset("2 blue"));
+    }
+}
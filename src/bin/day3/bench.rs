@@ -0,0 +1,78 @@
+use advent_of_code_2023::bench::measure;
+use nom::{
+    branch::alt, bytes::complete::is_a, character::complete::line_ending, combinator::map,
+    multi::many0, multi::separated_list1,
+};
+
+use super::{
+    tokenizer::{number, symbol, tokens, Token, TokenType, TokenizerOptions},
+    LocatedNumber, SymbolInfo,
+};
+
+/// The pipeline the lazy tokenizer replaced: every token, runs of dots included, is collected
+/// up front, the token list is cloned for each pass and symbols are copied into owned strings.
+fn eager(input: &str, options: &TokenizerOptions) -> usize {
+    let single_line_tokens = many0(alt((
+        map(number(options.signed_numbers), Some),
+        map(symbol(&options.operators), Some),
+        map(is_a("."), |_| None),
+    )));
+    let (_, lines) = separated_list1(line_ending, single_line_tokens)(input.into())
+        .expect("could not parse tokens");
+    let tokens: Vec<Option<Token>> = lines.into_iter().flatten().collect();
+
+    let number_locations = tokens
+        .iter()
+        .flatten()
+        .filter_map(|token| match token.kind {
+            TokenType::Number(value) => Some(LocatedNumber::new(*token, value)),
+            TokenType::Symbol => None,
+        })
+        .collect::<Vec<_>>();
+    let symbols = tokens
+        .clone()
+        .iter()
+        .flatten()
+        .filter(|token| token.kind == TokenType::Symbol)
+        .map(|token| (SymbolInfo::new(*token).location, token.value.to_owned()))
+        .collect::<Vec<_>>();
+    let gears = symbols
+        .clone()
+        .into_iter()
+        .filter(|(_, value)| value == "*")
+        .count();
+
+    number_locations.len() + symbols.len() + gears
+}
+
+fn lazy(input: &str, options: &TokenizerOptions) -> usize {
+    let mut number_locations = Vec::new();
+    let mut symbols = Vec::new();
+
+    for token in tokens(input, options) {
+        let token = token.expect("could not parse tokens");
+        match token.kind {
+            TokenType::Number(value) => number_locations.push(LocatedNumber::new(token, value)),
+            TokenType::Symbol => symbols.push(SymbolInfo::new(token)),
+        }
+    }
+    let gears = symbols
+        .iter()
+        .filter(|symbol_info| symbol_info.value == "*")
+        .count();
+
+    number_locations.len() + symbols.len() + gears
+}
+
+/// Tokenizes `copies` copies of the schematic stacked on top of each other with both pipelines.
+pub fn run(input: &str, options: &TokenizerOptions, copies: usize) {
+    let large = vec![input.trim_end(); copies].join("\n");
+    println!("{} rows, {} bytes", large.lines().count(), large.len());
+
+    for (name, pipeline) in [
+        ("eager", eager as fn(&str, &TokenizerOptions) -> usize),
+        ("lazy", lazy),
+    ] {
+        println!("{}", measure(|| pipeline(&large, options)).report(name));
+    }
+}
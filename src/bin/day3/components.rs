@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+
+use advent_of_code_2023::grid::Neighborhood;
+
+use super::{index::SpatialIndex, LocatedNumber, SymbolInfo};
+
+/// Numbers and symbols linked to each other through chains of adjacency.
+#[derive(Debug)]
+pub struct Assembly<'a> {
+    pub numbers: Vec<&'a LocatedNumber>,
+    pub symbols: Vec<&'a SymbolInfo<'a>>,
+}
+
+impl Assembly<'_> {
+    pub fn size(&self) -> usize {
+        self.numbers.len() + self.symbols.len()
+    }
+
+    /// Sum of the numbers, or `None` when it does not fit in an `i64`.
+    pub fn total_value(&self) -> Option<i64> {
+        self.numbers.iter().try_fold(0i64, |sum, located_number| {
+            sum.checked_add(located_number.value)
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Components<'a> {
+    pub assemblies: Vec<Assembly<'a>>,
+    /// Numbers not adjacent to any symbol.
+    pub orphan_numbers: Vec<&'a LocatedNumber>,
+    /// Symbols not adjacent to any number.
+    pub lone_symbols: Vec<&'a SymbolInfo<'a>>,
+}
+
+/// Union-find over the numbers followed by the symbols.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut node = node;
+        while self.parents[node] != root {
+            let parent = self.parents[node];
+            self.parents[node] = root;
+            node = parent;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+/// Splits the graph of numbers and symbols, linked whenever a number lies in the neighborhood of
+/// a symbol, into its connected components.
+pub fn connected_components<'a>(
+    numbers: &'a [LocatedNumber],
+    symbols: &'a [SymbolInfo<'a>],
+    index: &SpatialIndex,
+    neighborhood: Neighborhood,
+) -> Components<'a> {
+    let number_ids: HashMap<&LocatedNumber, usize> = numbers
+        .iter()
+        .enumerate()
+        .map(|(id, located_number)| (located_number, id))
+        .collect();
+    let mut sets = DisjointSets::new(numbers.len() + symbols.len());
+    let mut linked = vec![false; numbers.len() + symbols.len()];
+
+    for (symbol_id, symbol_info) in symbols.iter().enumerate() {
+        let symbol_node = numbers.len() + symbol_id;
+        for located_number in symbol_info.get_adjacent_numbers(index, neighborhood) {
+            let number_node = number_ids[&located_number];
+            sets.union(symbol_node, number_node);
+            linked[symbol_node] = true;
+            linked[number_node] = true;
+        }
+    }
+
+    // keyed by the first number of each component, to report them in reading order
+    let mut assemblies: BTreeMap<usize, Assembly> = BTreeMap::new();
+    let mut first_seen: HashMap<usize, usize> = HashMap::new();
+    let mut orphan_numbers = Vec::new();
+    let mut lone_symbols = Vec::new();
+
+    for (id, located_number) in numbers.iter().enumerate() {
+        if !linked[id] {
+            orphan_numbers.push(located_number);
+            continue;
+        }
+        let root = sets.find(id);
+        let order = *first_seen.entry(root).or_insert(id);
+        assemblies
+            .entry(order)
+            .or_insert_with(|| Assembly {
+                numbers: Vec::new(),
+                symbols: Vec::new(),
+            })
+            .numbers
+            .push(located_number);
+    }
+    for (symbol_id, symbol_info) in symbols.iter().enumerate() {
+        let node = numbers.len() + symbol_id;
+        if !linked[node] {
+            lone_symbols.push(symbol_info);
+            continue;
+        }
+        let root = sets.find(node);
+        assemblies
+            .get_mut(&first_seen[&root])
+            .expect("every linked symbol is linked to a number")
+            .symbols
+            .push(symbol_info);
+    }
+
+    Components {
+        assemblies: assemblies.into_values().collect(),
+        orphan_numbers,
+        lone_symbols,
+    }
+}
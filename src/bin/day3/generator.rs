@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use super::{
+    gear_ratios, gear_ratios_sum, index::SpatialIndex, part_numbers_sum, read_schematic, row_lens,
+    tokenizer::TokenizerOptions, Rules,
+};
+
+/// SplitMix64, good enough to make reproducible schematics from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeneratorConfig {
+    pub rows: usize,
+    pub columns: usize,
+    /// Chance that a number starts on any free cell.
+    pub number_density: f64,
+    /// Chance that any cell not taken by a number holds a symbol.
+    pub symbol_density: f64,
+    /// Chance that a symbol is a `*`.
+    pub gear_frequency: f64,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            rows: 140,
+            columns: 140,
+            number_density: 0.1,
+            symbol_density: 0.05,
+            gear_frequency: 0.3,
+        }
+    }
+}
+
+const SYMBOLS: &[u8] = b"#$%&+-/=@";
+
+/// A symbol with probability `config.symbol_density`, a dot otherwise.
+fn filler(config: &GeneratorConfig, rng: &mut Rng) -> char {
+    if rng.next_f64() >= config.symbol_density {
+        '.'
+    } else if rng.next_f64() < config.gear_frequency {
+        '*'
+    } else {
+        char::from(SYMBOLS[rng.below(SYMBOLS.len())])
+    }
+}
+
+/// Random schematic of `config.rows` lines of `config.columns` cells each. Numbers are one to
+/// three digits long and always followed by a dot or a symbol, so that they never run together.
+pub fn generate(config: &GeneratorConfig, rng: &mut Rng) -> String {
+    let mut schematic = String::with_capacity(config.rows * (config.columns + 1));
+
+    for _ in 0..config.rows {
+        let mut column = 0;
+        while column < config.columns {
+            if rng.next_f64() < config.number_density {
+                let len = (1 + rng.below(3)).min(config.columns - column);
+                for _ in 0..len {
+                    schematic.push(char::from(b'0' + rng.below(10) as u8));
+                }
+                column += len;
+                if column == config.columns {
+                    break;
+                }
+            }
+            schematic.push(filler(config, rng));
+            column += 1;
+        }
+        schematic.push('\n');
+    }
+
+    schematic
+}
+
+/// Part 1 and part 2 answers under the default rules, found by looking at every cell around
+/// every digit without the tokenizer or the spatial index.
+pub fn brute_force(schematic: &str) -> (i64, i64) {
+    let cells: Vec<Vec<char>> = schematic
+        .lines()
+        .map(|line| line.chars().collect())
+        .collect();
+    let is_symbol = |ch: char| !ch.is_ascii_digit() && ch != '.';
+
+    let mut part_numbers_sum = 0;
+    let mut gear_numbers: HashMap<(usize, usize), Vec<i64>> = HashMap::new();
+
+    for (row, line) in cells.iter().enumerate() {
+        let mut column = 0;
+        while column < line.len() {
+            if !line[column].is_ascii_digit() {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < line.len() && line[column].is_ascii_digit() {
+                column += 1;
+            }
+            let value = line[start..column]
+                .iter()
+                .collect::<String>()
+                .parse::<i64>()
+                .expect("a run of digits is a number");
+
+            let mut neighbors = Vec::new();
+            for neighbor_row in row.saturating_sub(1)..=row + 1 {
+                for neighbor_column in start.saturating_sub(1)..=column {
+                    let Some(ch) = cells
+                        .get(neighbor_row)
+                        .and_then(|cells| cells.get(neighbor_column))
+                    else {
+                        continue;
+                    };
+                    if is_symbol(*ch) {
+                        neighbors.push((neighbor_row, neighbor_column, *ch));
+                    }
+                }
+            }
+
+            if !neighbors.is_empty() {
+                part_numbers_sum += value;
+            }
+            for (neighbor_row, neighbor_column, ch) in neighbors {
+                if ch == '*' {
+                    gear_numbers
+                        .entry((neighbor_row, neighbor_column))
+                        .or_default()
+                        .push(value);
+                }
+            }
+        }
+    }
+
+    let gear_ratios_sum = gear_numbers
+        .values()
+        .filter(|numbers| numbers.len() == 2)
+        .map(|numbers| numbers[0] * numbers[1])
+        .sum();
+
+    (part_numbers_sum, gear_ratios_sum)
+}
+
+/// Part 1 and part 2 answers of the real pipeline under the default rules.
+fn solve(schematic: &str) -> (i64, i64) {
+    let rules = Rules::default();
+    let (number_locations, symbols) =
+        read_schematic(schematic, &rules, &TokenizerOptions::default())
+            .expect("generated schematics are well formed");
+    let index = SpatialIndex::new(row_lens(schematic), &number_locations, &symbols);
+
+    let gear_ratios =
+        gear_ratios(&symbols, &index, &rules).expect("three digit numbers do not overflow");
+    (
+        part_numbers_sum(&number_locations, &index, &rules)
+            .expect("three digit numbers do not overflow"),
+        gear_ratios_sum(&gear_ratios).expect("three digit numbers do not overflow"),
+    )
+}
+
+/// Compares the real pipeline with the brute-force checker on `rounds` schematics, generated from
+/// consecutive seeds starting at `seed`. Prints every disagreement and returns how many there were.
+pub fn fuzz(seed: u64, rounds: u64, config: &GeneratorConfig) -> usize {
+    let mut mismatches = 0;
+
+    for seed in seed..seed + rounds {
+        let schematic = generate(config, &mut Rng::new(seed));
+        let expected = brute_force(&schematic);
+        let found = solve(&schematic);
+        if found != expected {
+            mismatches += 1;
+            println!("seed {seed}: expected {expected:?}, found {found:?}");
+        }
+    }
+
+    println!("{rounds} schematics, {mismatches} mismatches");
+    mismatches
+}
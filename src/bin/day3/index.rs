@@ -0,0 +1,214 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use advent_of_code_2023::grid::{Location, Neighborhood};
+
+use super::{LocatedNumber, SymbolInfo};
+
+/// Position of an item in its slice, spanning `start..end` columns of a row.
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    start: usize,
+    end: usize,
+    id: usize,
+}
+
+/// Non-overlapping intervals of every row that has any, sorted by start column.
+#[derive(Debug, Default)]
+struct RowIntervals(BTreeMap<usize, Vec<Interval>>);
+
+impl RowIntervals {
+    fn new(items: impl Iterator<Item = (Location, usize)>) -> Self {
+        let mut rows: BTreeMap<usize, Vec<Interval>> = BTreeMap::new();
+
+        for (id, (location, len)) in items.enumerate() {
+            rows.entry(location.row).or_default().push(Interval {
+                start: location.column,
+                end: location.column + len,
+                id,
+            });
+        }
+        for intervals in rows.values_mut() {
+            intervals.sort_by_key(|interval| interval.start);
+        }
+
+        RowIntervals(rows)
+    }
+
+    /// Ids of the intervals on `row` overlapping `columns`.
+    fn overlapping(&self, row: usize, columns: Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        let intervals = self.0.get(&row).map_or(&[][..], Vec::as_slice);
+        // intervals do not overlap, so sorting by start sorts by end as well
+        let first = intervals.partition_point(|interval| interval.end <= columns.start);
+
+        intervals[first..]
+            .iter()
+            .take_while(move |interval| interval.start < columns.end)
+            .map(|interval| interval.id)
+    }
+}
+
+/// Numbers and symbols of a schematic indexed by row, so that adjacency is answered by binary
+/// search over the few tokens of the neighboring rows instead of by looking at every cell.
+pub struct SpatialIndex<'a> {
+    row_lens: Vec<usize>,
+    numbers: &'a [LocatedNumber],
+    symbols: &'a [SymbolInfo<'a>],
+    number_intervals: RowIntervals,
+    symbol_intervals: RowIntervals,
+}
+
+impl<'a> SpatialIndex<'a> {
+    pub fn new(
+        row_lens: Vec<usize>,
+        numbers: &'a [LocatedNumber],
+        symbols: &'a [SymbolInfo<'a>],
+    ) -> Self {
+        SpatialIndex {
+            row_lens,
+            numbers,
+            symbols,
+            number_intervals: RowIntervals::new(
+                numbers
+                    .iter()
+                    .map(|number| (number.start_location, number.len)),
+            ),
+            symbol_intervals: RowIntervals::new(
+                symbols.iter().map(|symbol| (symbol.location, symbol.len)),
+            ),
+        }
+    }
+
+    /// Ids of the items of `intervals` within `neighborhood` of the cells `location..location +
+    /// len`.
+    fn adjacent(
+        &self,
+        intervals: &RowIntervals,
+        location: Location,
+        len: usize,
+        neighborhood: Neighborhood,
+    ) -> Vec<usize> {
+        let mut ids = Vec::new();
+
+        let row_len = |row| self.row_lens.get(row).copied().unwrap_or_default();
+        for (row, ranges) in neighborhood.around(location, len, self.row_lens.len(), row_len) {
+            for columns in ranges {
+                ids.extend(intervals.overlapping(row, columns));
+            }
+        }
+
+        // wrapping around a small schematic can reach the same item from several sides
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn numbers_adjacent_to(
+        &self,
+        symbol_info: &SymbolInfo,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = &'a LocatedNumber> + '_ {
+        self.adjacent(
+            &self.number_intervals,
+            symbol_info.location,
+            symbol_info.len,
+            neighborhood,
+        )
+        .into_iter()
+        .map(|id| &self.numbers[id])
+    }
+
+    pub fn symbols_adjacent_to(
+        &self,
+        located_number: &LocatedNumber,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = &'a SymbolInfo<'a>> + '_ {
+        self.adjacent(
+            &self.symbol_intervals,
+            located_number.start_location,
+            located_number.len,
+            neighborhood,
+        )
+        .into_iter()
+        .map(|id| &self.symbols[id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use advent_of_code_2023::grid::Connectivity;
+
+    use super::*;
+    use crate::{read_schematic, row_lens, tokenizer::TokenizerOptions, Rules};
+
+    // ragged rows of 6, 3, 7 and 1 cells
+    const SCHEMATIC: &str = "12...#\n..$\n......4\n%";
+
+    fn neighborhood(connectivity: Connectivity, radius: usize, wrap: bool) -> Neighborhood {
+        Neighborhood {
+            connectivity,
+            radius,
+            wrap,
+        }
+    }
+
+    /// Symbols around the number `number` and numbers around the symbol `symbol` in
+    /// `neighborhood`, by value.
+    fn around(number: i64, symbol: &str, neighborhood: Neighborhood) -> (String, Vec<i64>) {
+        let (numbers, symbols) =
+            read_schematic(SCHEMATIC, &Rules::default(), &TokenizerOptions::default()).unwrap();
+        let index = SpatialIndex::new(row_lens(SCHEMATIC), &numbers, &symbols);
+
+        let number = numbers.iter().find(|n| n.value == number).unwrap();
+        let symbol = symbols.iter().find(|s| s.value == symbol).unwrap();
+        (
+            index
+                .symbols_adjacent_to(number, neighborhood)
+                .map(|symbol_info| symbol_info.value)
+                .collect(),
+            index
+                .numbers_adjacent_to(symbol, neighborhood)
+                .map(|located_number| located_number.value)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn moore_reaches_diagonals() {
+        let moore = neighborhood(Connectivity::Eight, 1, false);
+        assert_eq!(around(12, "$", moore), ("$".to_owned(), vec![12]));
+        assert_eq!(around(4, "%", moore), ("".to_owned(), vec![]));
+    }
+
+    #[test]
+    fn von_neumann_skips_diagonals() {
+        let von_neumann = neighborhood(Connectivity::Four, 1, false);
+        assert_eq!(around(12, "$", von_neumann), ("".to_owned(), vec![]));
+
+        let von_neumann = neighborhood(Connectivity::Four, 2, false);
+        assert_eq!(around(12, "$", von_neumann), ("$".to_owned(), vec![12]));
+        assert_eq!(around(4, "#", von_neumann), ("".to_owned(), vec![]));
+    }
+
+    #[test]
+    fn larger_radius_reaches_further_rows() {
+        let moore = neighborhood(Connectivity::Eight, 2, false);
+        assert_eq!(around(12, "$", moore), ("$".to_owned(), vec![12]));
+        assert_eq!(around(4, "#", moore), ("#".to_owned(), vec![4]));
+        assert_eq!(around(12, "%", moore), ("$".to_owned(), vec![]));
+
+        let moore = neighborhood(Connectivity::Eight, 3, false);
+        assert_eq!(around(12, "%", moore), ("$%".to_owned(), vec![12]));
+    }
+
+    #[test]
+    fn wrapping_goes_around_ragged_rows() {
+        let moore = neighborhood(Connectivity::Eight, 1, true);
+        // row 3 is one cell long, and the left edge of row 0 continues at its right end
+        assert_eq!(around(12, "%", moore), ("#$%".to_owned(), vec![12, 4]));
+        // row 1 is shorter than the span around 4, which then covers all of it
+        assert_eq!(around(4, "$", moore), ("$%".to_owned(), vec![12]));
+
+        let von_neumann = neighborhood(Connectivity::Four, 2, true);
+        assert_eq!(around(4, "%", von_neumann), ("$%".to_owned(), vec![12, 4]));
+    }
+}
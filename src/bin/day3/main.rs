@@ -0,0 +1,663 @@
+mod bench;
+mod components;
+mod generator;
+mod index;
+mod render;
+mod tokenizer;
+
+use std::{collections::HashSet, hash::Hash};
+
+use advent_of_code_2023::{
+    grid::{Connectivity, Grid, Location, Neighborhood},
+    input,
+};
+use generator::GeneratorConfig;
+use index::SpatialIndex;
+use tokenizer::{tokens, MalformedSchematic, Token, TokenType, TokenizerOptions};
+
+#[derive(Debug, Clone)]
+struct SymbolInfo<'a> {
+    location: Location,
+    len: usize,
+    value: &'a str,
+}
+
+impl<'a> SymbolInfo<'a> {
+    fn new(token: Token<'a>) -> Self {
+        let row = token.position.location_line() as usize - 1;
+        let column = token.position.get_utf8_column() - 1;
+
+        SymbolInfo {
+            location: Location { row, column },
+            len: token.value.chars().count(),
+            value: token.value,
+        }
+    }
+    fn get_adjacent_numbers(
+        &self,
+        index: &SpatialIndex,
+        neighborhood: Neighborhood,
+    ) -> HashSet<LocatedNumber> {
+        index
+            .numbers_adjacent_to(self, neighborhood)
+            .copied()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct LocatedNumber {
+    start_location: Location,
+    len: usize,
+    // row: usize,
+    value: i64,
+}
+
+impl LocatedNumber {
+    fn new(token: Token, value: i64) -> Self {
+        let row = token.position.location_line() as usize - 1;
+        let column = token.position.get_utf8_column() - 1;
+
+        LocatedNumber {
+            start_location: Location { row, column },
+            len: token.value.len(),
+            // row,
+            value,
+        }
+    }
+
+    fn is_part(&self, index: &SpatialIndex, neighborhood: Neighborhood) -> bool {
+        index
+            .symbols_adjacent_to(self, neighborhood)
+            .next()
+            .is_some()
+    }
+}
+
+/// Which tokens count as symbols.
+#[derive(Debug)]
+enum SymbolClass {
+    /// Anything that is neither a digit nor a dot.
+    Any,
+    Only(HashSet<String>),
+}
+
+impl SymbolClass {
+    fn contains(&self, value: &str) -> bool {
+        match self {
+            SymbolClass::Any => true,
+            SymbolClass::Only(symbols) => symbols.contains(value),
+        }
+    }
+}
+
+/// How many adjacent numbers turn a gear symbol into a gear.
+#[derive(Debug, Clone, Copy)]
+enum AdjacencyCount {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl AdjacencyCount {
+    fn matches(&self, count: usize) -> bool {
+        match *self {
+            AdjacencyCount::Exactly(n) => count == n,
+            AdjacencyCount::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+/// How the numbers adjacent to a gear combine into its ratio.
+#[derive(Debug, Clone, Copy)]
+enum Aggregation {
+    Product,
+    Sum,
+}
+
+impl Aggregation {
+    /// Product or sum of `values`, or `None` when it does not fit in an `i64`.
+    fn apply(&self, mut values: impl Iterator<Item = i64>) -> Option<i64> {
+        match self {
+            Aggregation::Product => {
+                values.try_fold(1i64, |product, value| product.checked_mul(value))
+            }
+            Aggregation::Sum => values.try_fold(0i64, |sum, value| sum.checked_add(value)),
+        }
+    }
+}
+
+/// A sum or product of numbers that does not fit in an `i64`, reported at the number or gear
+/// that took it over.
+#[derive(Debug)]
+struct Overflow {
+    location: Location,
+}
+
+impl std::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: total too large to count",
+            self.location.row + 1,
+            self.location.column + 1
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Rules {
+    symbols: SymbolClass,
+    gears: HashSet<String>,
+    gear_adjacency: AdjacencyCount,
+    gear_aggregation: Aggregation,
+    neighborhood: Neighborhood,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            symbols: SymbolClass::Any,
+            gears: HashSet::from(["*".to_owned()]),
+            gear_adjacency: AdjacencyCount::Exactly(2),
+            gear_aggregation: Aggregation::Product,
+            neighborhood: Neighborhood::moore(1),
+        }
+    }
+}
+
+impl Rules {
+    /// Gear characters are symbols even when the symbol class leaves them out.
+    fn is_symbol(&self, value: &str) -> bool {
+        self.symbols.contains(value) || self.is_gear(value)
+    }
+
+    fn is_gear(&self, value: &str) -> bool {
+        self.gears.contains(value)
+    }
+
+    /// Ratio of `gear`, when it has as many adjacent numbers as gears need.
+    fn gear_ratio(
+        &self,
+        gear: &SymbolInfo,
+        adjacent_numbers: &HashSet<LocatedNumber>,
+    ) -> Option<Result<i64, Overflow>> {
+        self.gear_adjacency
+            .matches(adjacent_numbers.len())
+            .then(|| {
+                self.gear_aggregation
+                    .apply(
+                        adjacent_numbers
+                            .iter()
+                            .map(|located_number| located_number.value),
+                    )
+                    .ok_or(Overflow {
+                        location: gear.location,
+                    })
+            })
+    }
+}
+
+const USAGE: &str = "usage: day3 [--symbols <chars>] [--gears <chars>] \
+                     [--gear-adjacency <n | n+>] [--gear-aggregation <product | sum>] \
+                     [--neighborhood <moore | von-neumann>] [--radius <k>] [--wrap] \
+                     [--signed] [--operators <op,op,...>] [--render <ansi | html>] \
+                     [--components] [--bench <copies>] [--generate <seed>] \
+                     [--fuzz <rounds>] [--seed <n>] [--size <rows>x<columns>] \
+                     [--number-density <p>] [--symbol-density <p>] [--gear-frequency <p>] \
+                     [--input <path>]";
+
+/// Splits a list of symbols into single characters, keeping declared `operators` in one piece.
+fn symbol_set(mut value: &str, operators: &[String]) -> HashSet<String> {
+    let mut symbols = HashSet::new();
+
+    while let Some(ch) = value.chars().next() {
+        let symbol = operators
+            .iter()
+            .map(String::as_str)
+            .find(|operator| value.starts_with(operator))
+            .unwrap_or(&value[..ch.len_utf8()]);
+        symbols.insert(symbol.to_owned());
+        value = &value[symbol.len()..];
+    }
+
+    symbols
+}
+
+struct Config {
+    rules: Rules,
+    tokenizer: TokenizerOptions,
+    render: Option<render::Format>,
+    components: bool,
+    bench: Option<usize>,
+    generate: Option<u64>,
+    fuzz: Option<u64>,
+    seed: u64,
+    generator: GeneratorConfig,
+    input: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut rules = Rules::default();
+    let mut symbols: Option<String> = None;
+    let mut gears: Option<String> = None;
+    let mut signed_numbers = false;
+    let mut render = None;
+    let mut components = false;
+    let mut bench = None;
+    let mut operators: Vec<String> = Vec::new();
+    let mut generate = None;
+    let mut fuzz = None;
+    let mut seed = 0;
+    let mut generator = GeneratorConfig::default();
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} expects a value"));
+        match arg.as_str() {
+            "--symbols" => symbols = Some(value()?),
+            "--gears" => gears = Some(value()?),
+            "--gear-adjacency" => {
+                let value = value()?;
+                let count = |n: &str| {
+                    n.parse::<usize>()
+                        .map_err(|_| format!("could not parse adjacency count: {value}"))
+                };
+                rules.gear_adjacency = match value.strip_suffix('+') {
+                    Some(n) => AdjacencyCount::AtLeast(count(n)?),
+                    None => AdjacencyCount::Exactly(count(&value)?),
+                };
+            }
+            "--gear-aggregation" => {
+                rules.gear_aggregation = match value()?.as_str() {
+                    "product" => Aggregation::Product,
+                    "sum" => Aggregation::Sum,
+                    other => return Err(format!("unknown aggregation: {other}")),
+                }
+            }
+            "--neighborhood" => {
+                rules.neighborhood.connectivity = match value()?.as_str() {
+                    "moore" => Connectivity::Eight,
+                    "von-neumann" => Connectivity::Four,
+                    other => return Err(format!("unknown neighborhood: {other}")),
+                }
+            }
+            "--radius" => {
+                let value = value()?;
+                rules.neighborhood.radius = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("could not parse radius: {value}"))?;
+            }
+            "--wrap" => rules.neighborhood.wrap = true,
+            "--input" => input = Some(value()?),
+            "--signed" => signed_numbers = true,
+            "--components" => components = true,
+            "--bench" => {
+                let value = value()?;
+                let copies = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("could not parse number of copies: {value}"))?;
+                bench = Some(copies);
+            }
+            "--render" => {
+                render = match value()?.as_str() {
+                    "ansi" => Some(render::Format::Ansi),
+                    "html" => Some(render::Format::Html),
+                    other => return Err(format!("unknown render format: {other}")),
+                }
+            }
+            "--operators" => {
+                operators = value()?
+                    .split(',')
+                    .filter(|operator| !operator.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+            }
+            "--generate" => {
+                let value = value()?;
+                let seed = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("could not parse seed: {value}"))?;
+                generate = Some(seed);
+            }
+            "--fuzz" => {
+                let value = value()?;
+                let rounds = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("could not parse number of rounds: {value}"))?;
+                fuzz = Some(rounds);
+            }
+            "--seed" => {
+                let value = value()?;
+                seed = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("could not parse seed: {value}"))?;
+            }
+            "--size" => {
+                let value = value()?;
+                let (rows, columns) = value
+                    .split_once('x')
+                    .and_then(|(rows, columns)| {
+                        Some((rows.parse::<usize>().ok()?, columns.parse::<usize>().ok()?))
+                    })
+                    .ok_or(format!("could not parse size: {value}"))?;
+                generator.rows = rows;
+                generator.columns = columns;
+            }
+            "--number-density" | "--symbol-density" | "--gear-frequency" => {
+                let value = value()?;
+                let probability = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|probability| (0.0..=1.0).contains(probability))
+                    .ok_or(format!("could not parse probability: {value}"))?;
+                match arg.as_str() {
+                    "--number-density" => generator.number_density = probability,
+                    "--symbol-density" => generator.symbol_density = probability,
+                    _ => generator.gear_frequency = probability,
+                }
+            }
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+
+    let tokenizer = TokenizerOptions {
+        signed_numbers,
+        ..Default::default()
+    }
+    .with_operators(operators);
+
+    if let Some(symbols) = symbols {
+        let mut symbols = symbol_set(&symbols, &tokenizer.operators);
+        // operators are declared on purpose, so they always count as symbols
+        symbols.extend(tokenizer.operators.iter().cloned());
+        rules.symbols = SymbolClass::Only(symbols);
+    }
+    if let Some(gears) = gears {
+        rules.gears = symbol_set(&gears, &tokenizer.operators);
+    }
+
+    Ok(Config {
+        rules,
+        tokenizer,
+        render,
+        components,
+        bench,
+        generate,
+        fuzz,
+        seed,
+        generator,
+        input,
+    })
+}
+
+/// The numbers and the symbols, as selected by `rules`, of a schematic.
+fn read_schematic<'a>(
+    input: &'a str,
+    rules: &Rules,
+    tokenizer: &TokenizerOptions,
+) -> Result<(Vec<LocatedNumber>, Vec<SymbolInfo<'a>>), MalformedSchematic> {
+    let mut number_locations: Vec<LocatedNumber> = Vec::new();
+    let mut symbols: Vec<SymbolInfo> = Vec::new();
+
+    for token in tokens(input, tokenizer) {
+        let token = token?;
+        if DEBUG {
+            println!("{token:?}");
+        }
+        match token.kind {
+            TokenType::Number(value) => number_locations.push(LocatedNumber::new(token, value)),
+            TokenType::Symbol if rules.is_symbol(token.value) => {
+                symbols.push(SymbolInfo::new(token))
+            }
+            TokenType::Symbol => {}
+        }
+    }
+
+    Ok((number_locations, symbols))
+}
+
+fn row_lens(input: &str) -> Vec<usize> {
+    input.lines().map(|line| line.chars().count()).collect()
+}
+
+fn part_numbers_sum(
+    number_locations: &[LocatedNumber],
+    index: &SpatialIndex,
+    rules: &Rules,
+) -> Result<i64, Overflow> {
+    number_locations
+        .iter()
+        .filter(|located_number| located_number.is_part(index, rules.neighborhood))
+        .try_fold(0i64, |sum, located_number| {
+            sum.checked_add(located_number.value).ok_or(Overflow {
+                location: located_number.start_location,
+            })
+        })
+}
+
+fn gear_ratios<'s, 'a>(
+    symbols: &'s [SymbolInfo<'a>],
+    index: &SpatialIndex,
+    rules: &Rules,
+) -> Result<Vec<(&'s SymbolInfo<'a>, i64)>, Overflow> {
+    symbols
+        .iter()
+        .filter(|symbol_info| rules.is_gear(symbol_info.value))
+        .filter_map(|gear| {
+            rules
+                .gear_ratio(gear, &gear.get_adjacent_numbers(index, rules.neighborhood))
+                .map(|ratio| Ok((gear, ratio?)))
+        })
+        .collect()
+}
+
+fn gear_ratios_sum(gear_ratios: &[(&SymbolInfo, i64)]) -> Result<i64, Overflow> {
+    gear_ratios.iter().try_fold(0i64, |sum, (gear, ratio)| {
+        sum.checked_add(*ratio).ok_or(Overflow {
+            location: gear.location,
+        })
+    })
+}
+
+const DEBUG: bool = false;
+fn main() {
+    let Config {
+        rules,
+        tokenizer,
+        render,
+        components,
+        bench,
+        generate,
+        fuzz,
+        seed,
+        generator,
+        input: input_path,
+    } = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    });
+
+    if let Some(seed) = generate {
+        let schematic = generator::generate(&generator, &mut generator::Rng::new(seed));
+        let (answer_p1, answer_p2) = generator::brute_force(&schematic);
+        print!("{schematic}");
+        eprintln!("{answer_p1}");
+        eprintln!("{answer_p2}");
+        return;
+    }
+    if let Some(rounds) = fuzz {
+        let mismatches = generator::fuzz(seed, rounds, &generator);
+        std::process::exit(if mismatches == 0 { 0 } else { 1 });
+    }
+
+    let input = input::load(
+        input_path.as_deref(),
+        include_str!("../../../inputs/real/day3.txt"),
+    );
+    let input: &str = &input;
+
+    if let Some(copies) = bench {
+        bench::run(input, &tokenizer, copies);
+        return;
+    }
+
+    let (number_locations, symbols) =
+        read_schematic(input, &rules, &tokenizer).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1);
+        });
+
+    if DEBUG {
+        for located_number in &number_locations {
+            println!("{located_number:?}");
+        }
+        println!();
+        for symbol_info in &symbols {
+            println!("{symbol_info:?}");
+        }
+        println!();
+    }
+
+    let index = SpatialIndex::new(row_lens(input), &number_locations, &symbols);
+
+    let (answer_p1, gear_ratios, answer_p2) = part_numbers_sum(&number_locations, &index, &rules)
+        .and_then(|answer_p1| {
+            let gear_ratios = gear_ratios(&symbols, &index, &rules)?;
+            let answer_p2 = gear_ratios_sum(&gear_ratios)?;
+            Ok((answer_p1, gear_ratios, answer_p2))
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1);
+        });
+
+    if let Some(format) = render {
+        let schematic = Grid::parse(input);
+        let mut annotated = render::Annotated::new(&schematic);
+        for located_number in &number_locations {
+            annotated.mark_number(
+                located_number,
+                located_number.is_part(&index, rules.neighborhood),
+            );
+        }
+        for symbol_info in &symbols {
+            annotated.mark_symbol(symbol_info);
+        }
+        for (gear, ratio) in &gear_ratios {
+            annotated.mark_gear(gear, *ratio);
+        }
+        print!("{}", annotated.render(format));
+    }
+
+    if components {
+        let components = components::connected_components(
+            &number_locations,
+            &symbols,
+            &index,
+            rules.neighborhood,
+        );
+        for (n, assembly) in components.assemblies.iter().enumerate() {
+            println!(
+                "assembly {}: {} numbers, {} symbols, total {}",
+                n + 1,
+                assembly.numbers.len(),
+                assembly.symbols.len(),
+                assembly
+                    .total_value()
+                    .map_or("too large to count".to_owned(), |total| total.to_string())
+            );
+        }
+        for located_number in &components.orphan_numbers {
+            let Location { row, column } = located_number.start_location;
+            println!(
+                "orphan number {} at {}:{}",
+                located_number.value,
+                row + 1,
+                column + 1
+            );
+        }
+        println!(
+            "{} assemblies, largest has {} parts, {} orphan numbers, {} lone symbols",
+            components.assemblies.len(),
+            components
+                .assemblies
+                .iter()
+                .map(components::Assembly::size)
+                .max()
+                .unwrap_or_default(),
+            components.orphan_numbers.len(),
+            components.lone_symbols.len()
+        );
+    }
+
+    println!("{answer_p1}");
+    println!("{answer_p2}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Part 1 and part 2 answers for `input` under the rules given by command line `args`.
+    fn answers(args: &[&str], input: &str) -> (i64, i64) {
+        let config = parse_args(args.iter().map(|arg| arg.to_string())).unwrap();
+        let (number_locations, symbols) =
+            read_schematic(input, &config.rules, &config.tokenizer).unwrap();
+        let index = SpatialIndex::new(row_lens(input), &number_locations, &symbols);
+        let gear_ratios = gear_ratios(&symbols, &index, &config.rules).unwrap();
+
+        (
+            part_numbers_sum(&number_locations, &index, &config.rules).unwrap(),
+            gear_ratios_sum(&gear_ratios).unwrap(),
+        )
+    }
+
+    #[test]
+    fn signed_numbers() {
+        assert_eq!(answers(&["--signed"], "-42*3"), (-39, -126));
+        assert_eq!(answers(&[], "-42*3"), (45, 126));
+    }
+
+    #[test]
+    fn multi_character_operators() {
+        assert_eq!(
+            answers(&["--operators", "**", "--gears", "**"], "2**3"),
+            (5, 6)
+        );
+        assert_eq!(answers(&[], "2**3"), (5, 0));
+        // operators are symbols even when the symbol class leaves out their characters
+        assert_eq!(
+            answers(&["--symbols", "#", "--operators", "->"], "1->.\n....\n2-.."),
+            (1, 0)
+        );
+    }
+
+    #[test]
+    fn pipeline_agrees_with_brute_force() {
+        let dense = GeneratorConfig {
+            rows: 7,
+            columns: 5,
+            number_density: 0.5,
+            symbol_density: 0.5,
+            gear_frequency: 0.5,
+        };
+        let configs = [
+            dense,
+            GeneratorConfig {
+                rows: 3,
+                columns: 1,
+                ..dense
+            },
+            GeneratorConfig {
+                rows: 20,
+                columns: 30,
+                ..GeneratorConfig::default()
+            },
+        ];
+
+        for config in &configs {
+            assert_eq!(generator::fuzz(0, 300, config), 0, "{config:?}");
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use advent_of_code_2023::grid::{Grid, Location};
+
+use super::{LocatedNumber, SymbolInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Highlight {
+    Plain,
+    PartNumber,
+    NonPartNumber,
+    Symbol,
+    Gear,
+}
+
+impl Highlight {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Highlight::Plain => "\x1b[2m",
+            Highlight::PartNumber => "\x1b[32m",
+            Highlight::NonPartNumber => "\x1b[31m",
+            Highlight::Symbol => "\x1b[33m",
+            Highlight::Gear => "\x1b[1;35m",
+        }
+    }
+
+    fn css_class(&self) -> &'static str {
+        match self {
+            Highlight::Plain => "plain",
+            Highlight::PartNumber => "part",
+            Highlight::NonPartNumber => "non-part",
+            Highlight::Symbol => "symbol",
+            Highlight::Gear => "gear",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Ansi,
+    Html,
+}
+
+/// The schematic with every cell marked by what it turned out to be.
+pub struct Annotated<'a> {
+    schematic: &'a Grid<char>,
+    highlights: Grid<Highlight>,
+    gear_ratios: BTreeMap<Location, i64>,
+}
+
+impl<'a> Annotated<'a> {
+    pub fn new(schematic: &'a Grid<char>) -> Self {
+        Annotated {
+            schematic,
+            highlights: schematic.map(|_| Highlight::Plain),
+            gear_ratios: BTreeMap::new(),
+        }
+    }
+
+    fn mark(&mut self, start_location: Location, len: usize, highlight: Highlight) {
+        for column in start_location.column..start_location.column + len {
+            let location = Location::new(start_location.row, column);
+            if let Some(cell) = self.highlights.get_mut(location) {
+                *cell = highlight;
+            }
+        }
+    }
+
+    pub fn mark_number(&mut self, located_number: &LocatedNumber, is_part: bool) {
+        let highlight = match is_part {
+            true => Highlight::PartNumber,
+            false => Highlight::NonPartNumber,
+        };
+        self.mark(located_number.start_location, located_number.len, highlight);
+    }
+
+    pub fn mark_symbol(&mut self, symbol_info: &SymbolInfo) {
+        self.mark(symbol_info.location, symbol_info.len, Highlight::Symbol);
+    }
+
+    pub fn mark_gear(&mut self, symbol_info: &SymbolInfo, ratio: i64) {
+        self.mark(symbol_info.location, symbol_info.len, Highlight::Gear);
+        self.gear_ratios.insert(symbol_info.location, ratio);
+    }
+
+    /// Runs of equally highlighted cells in the given row.
+    fn spans(&self, row: usize) -> Vec<(Highlight, String)> {
+        let mut spans: Vec<(Highlight, String)> = Vec::new();
+
+        for column in 0..self.schematic.row_len(row) {
+            let location = Location::new(row, column);
+            let highlight = self.highlights[location];
+            let ch = self.schematic[location];
+            // every gear gets a span of its own so that it can carry its ratio
+            let starts_gear = self.gear_ratios.contains_key(&location);
+            match spans.last_mut() {
+                Some((last, text)) if *last == highlight && !starts_gear => text.push(ch),
+                _ => spans.push((highlight, ch.to_string())),
+            }
+        }
+
+        spans
+    }
+
+    fn ratios_in_row(&self, row: usize) -> impl Iterator<Item = (&Location, &i64)> {
+        self.gear_ratios
+            .range(Location::new(row, 0)..Location::new(row + 1, 0))
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Ansi => self.to_ansi(),
+            Format::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+
+        for row in 0..self.schematic.height() {
+            for (highlight, text) in self.spans(row) {
+                write!(out, "{}{text}\x1b[0m", highlight.ansi()).unwrap();
+            }
+            for (location, ratio) in self.ratios_in_row(row) {
+                write!(
+                    out,
+                    "  {}gear {}:{} = {ratio}\x1b[0m",
+                    Highlight::Gear.ansi(),
+                    location.row + 1,
+                    location.column + 1
+                )
+                .unwrap();
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<style>\n",
+            "pre { background: #111; color: #eee; }\n",
+            ".plain { color: #555; }\n",
+            ".part { color: #4c4; }\n",
+            ".non-part { color: #e44; }\n",
+            ".symbol { color: #dd4; }\n",
+            ".gear { color: #d4d; font-weight: bold; }\n",
+            "</style>\n</head>\n<body>\n<pre>\n",
+        ));
+
+        for row in 0..self.schematic.height() {
+            let mut column = 0;
+            for (highlight, text) in self.spans(row) {
+                let title = match self.gear_ratios.get(&Location::new(row, column)) {
+                    Some(ratio) => format!(" title=\"ratio {ratio}\""),
+                    None => String::new(),
+                };
+                column += text.chars().count();
+                write!(
+                    out,
+                    "<span class=\"{}\"{title}>{}</span>",
+                    highlight.css_class(),
+                    escape_html(&text)
+                )
+                .unwrap();
+            }
+            out.push('\n');
+        }
+
+        out.push_str("</pre>\n</body>\n</html>\n");
+        out
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+
+use nom::{
+    self,
+    branch::alt,
+    bytes::complete::{is_a, tag},
+    character::complete::{anychar, digit1, line_ending, one_of},
+    combinator::{consumed, map, opt, recognize, verify},
+    error::ErrorKind,
+    multi::many0_count,
+    sequence::pair,
+    IResult,
+};
+use nom_locate::LocatedSpan;
+
+use advent_of_code_2023::parsing::{self, signed_number};
+
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TokenType {
+    Number(i64),
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Token<'a> {
+    pub kind: TokenType,
+    pub position: Span<'a>,
+    pub value: &'a str,
+}
+
+/// Optional extensions to the plain digits-and-single-characters tokenizer.
+#[derive(Debug, Default)]
+pub struct TokenizerOptions {
+    /// Read a `+` or `-` directly in front of digits as the sign of the number.
+    pub signed_numbers: bool,
+    /// Multi-character symbols, tried longest first before falling back to a single character.
+    pub operators: Vec<String>,
+}
+
+impl TokenizerOptions {
+    pub fn with_operators(mut self, mut operators: Vec<String>) -> Self {
+        operators.sort_by_key(|operator| std::cmp::Reverse(operator.len()));
+        self.operators = operators;
+        self
+    }
+}
+
+/// Digits of a number, sign included when `signed`, whether or not they fit in an `i64`.
+fn digits<'a>(signed: bool) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    recognize(pair(opt(verify(one_of("+-"), move |_| signed)), digit1))
+}
+
+/// Number token holding its value. Digits that do not fit in an `i64` fail with their span as
+/// the error input, leaving no other token to try.
+pub fn number<'a>(signed: bool) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Token<'a>> {
+    let parse_value = move |s: Span<'a>| {
+        if signed {
+            signed_number(s)
+        } else {
+            parsing::number(s)
+        }
+    };
+
+    let token_number = |(s, value): (Span<'a>, i64)| -> Token<'a> {
+        Token {
+            kind: TokenType::Number(value),
+            position: s,
+            value: s.fragment(),
+        }
+    };
+
+    move |s: Span<'a>| {
+        let (_, digits) = digits(signed)(s)?;
+        map(consumed(parse_value), token_number)(s).map_err(|_: nom::Err<nom::error::Error<_>>| {
+            nom::Err::Failure(nom::error::Error::new(digits, ErrorKind::TooLarge))
+        })
+    }
+}
+
+fn operator<'a, 'o>(
+    operators: &'o [String],
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> + 'o {
+    move |s: Span<'a>| {
+        for operator in operators {
+            if let Ok(parsed) = tag::<_, _, nom::error::Error<_>>(operator.as_str())(s) {
+                return Ok(parsed);
+            }
+        }
+        Err(nom::Err::Error(nom::error::Error::new(s, ErrorKind::Tag)))
+    }
+}
+
+pub fn symbol<'a, 'o>(
+    operators: &'o [String],
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Token<'a>> + 'o
+where
+    'a: 'o,
+{
+    // whitespace, including the `\r` of CRLF line endings, is never a symbol
+    let single_symbol = recognize(verify(anychar, |ch: &char| {
+        !ch.is_ascii_digit() && *ch != '.' && !ch.is_whitespace()
+    }));
+    let parse_symbol = alt((operator(operators), single_symbol));
+
+    let token_symbol = |s: Span<'a>| -> Token<'a> {
+        Token {
+            kind: TokenType::Symbol,
+            position: s,
+            value: s.fragment(),
+        }
+    };
+    map(parse_symbol, token_symbol)
+}
+
+/// Runs of dots and line endings between tokens.
+pub fn gap(s: Span<'_>) -> IResult<Span<'_>, usize> {
+    many0_count(alt((is_a("."), line_ending)))(s)
+}
+
+#[derive(Debug)]
+enum Problem {
+    Unexpected(char),
+    NumberOutOfRange(String),
+}
+
+/// Position of the first token the tokenizer could not make sense of.
+#[derive(Debug)]
+pub struct MalformedSchematic {
+    line: u32,
+    column: usize,
+    problem: Problem,
+}
+
+impl MalformedSchematic {
+    pub fn at(s: Span) -> Self {
+        MalformedSchematic {
+            line: s.location_line(),
+            column: s.get_utf8_column(),
+            problem: Problem::Unexpected(s.fragment().chars().next().unwrap_or_default()),
+        }
+    }
+
+    /// Error for the `digits` of a number that does not fit in an `i64`.
+    pub fn out_of_range(digits: Span) -> Self {
+        MalformedSchematic {
+            problem: Problem::NumberOutOfRange(digits.fragment().to_string()),
+            ..MalformedSchematic::at(digits)
+        }
+    }
+}
+
+impl fmt::Display for MalformedSchematic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "malformed schematic at line {}, column {}: ",
+            self.line, self.column
+        )?;
+        match &self.problem {
+            Problem::Unexpected(found) => write!(f, "unexpected {found:?}"),
+            Problem::NumberOutOfRange(digits) => write!(
+                f,
+                "number {digits} out of range, spanning {} columns",
+                digits.chars().count()
+            ),
+        }
+    }
+}
+
+/// Lazily yields the numbers and symbols of a schematic, borrowing them from the input. Dots
+/// and line endings are skipped without producing anything. After reporting a malformed
+/// schematic the iterator is exhausted.
+pub struct Tokens<'a, 'o> {
+    rest: Span<'a>,
+    options: &'o TokenizerOptions,
+    failed: bool,
+}
+
+pub fn tokens<'a, 'o>(input: &'a str, options: &'o TokenizerOptions) -> Tokens<'a, 'o> {
+    Tokens {
+        rest: input.into(),
+        options,
+        failed: false,
+    }
+}
+
+impl<'a, 'o> Iterator for Tokens<'a, 'o>
+where
+    'a: 'o,
+{
+    type Item = Result<Token<'a>, MalformedSchematic>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if let Ok((rest, _)) = gap(self.rest) {
+            self.rest = rest;
+        }
+        if self.rest.fragment().is_empty() {
+            return None;
+        }
+
+        let mut parse_token = alt((
+            number(self.options.signed_numbers),
+            symbol(&self.options.operators),
+        ));
+        match parse_token(self.rest) {
+            Ok((rest, token)) => {
+                self.rest = rest;
+                Some(Ok(token))
+            }
+            Err(nom::Err::Failure(err)) => {
+                self.failed = true;
+                Some(Err(MalformedSchematic::out_of_range(err.input)))
+            }
+            Err(_) => {
+                self.failed = true;
+                Some(Err(MalformedSchematic::at(self.rest)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value, line and column of every token, or the error that stopped the tokenizer.
+    fn positions(
+        input: &str,
+        options: &TokenizerOptions,
+    ) -> Result<Vec<(String, u32, usize)>, String> {
+        tokens(input, options)
+            .map(|token| {
+                let token = token.map_err(|err| err.to_string())?;
+                Ok((
+                    token.value.to_owned(),
+                    token.position.location_line(),
+                    token.position.get_utf8_column(),
+                ))
+            })
+            .collect()
+    }
+
+    fn token(value: &str, line: u32, column: usize) -> (String, u32, usize) {
+        (value.to_owned(), line, column)
+    }
+
+    #[test]
+    fn crlf_line_endings_are_skipped() {
+        let found = positions("467..114\r\n...*....\r\n", &TokenizerOptions::default());
+        assert_eq!(
+            found,
+            Ok(vec![
+                token("467", 1, 1),
+                token("114", 1, 6),
+                token("*", 2, 4)
+            ])
+        );
+    }
+
+    #[test]
+    fn ragged_rows_keep_their_columns() {
+        let found = positions("12\n.....#\n3\n..$", &TokenizerOptions::default());
+        assert_eq!(
+            found,
+            Ok(vec![
+                token("12", 1, 1),
+                token("#", 2, 6),
+                token("3", 3, 1),
+                token("$", 4, 3),
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_schematic_is_located() {
+        let options = TokenizerOptions::default();
+        let mut found = tokens("1.*\n.2 3", &options);
+
+        assert!(found.next().unwrap().is_ok());
+        assert!(found.next().unwrap().is_ok());
+        assert!(found.next().unwrap().is_ok());
+        let err = found.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "malformed schematic at line 2, column 3: unexpected ' '"
+        );
+        assert!(found.next().is_none());
+    }
+
+    #[test]
+    fn numbers_out_of_range_are_reported_as_such() {
+        let found = positions("..99999999999999999999*", &TokenizerOptions::default());
+        assert_eq!(
+            found,
+            Err(
+                "malformed schematic at line 1, column 3: number 99999999999999999999 out of \
+                 range, spanning 20 columns"
+                    .to_owned()
+            )
+        );
+
+        let signed = TokenizerOptions {
+            signed_numbers: true,
+            ..TokenizerOptions::default()
+        };
+        let found = positions("1\n-9223372036854775809", &signed);
+        assert_eq!(
+            found,
+            Err(
+                "malformed schematic at line 2, column 1: number -9223372036854775809 out of \
+                 range, spanning 20 columns"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            positions("-9223372036854775808", &signed),
+            Ok(vec![token("-9223372036854775808", 1, 1)])
+        );
+    }
+
+    #[test]
+    fn signs_belong_to_numbers_only_when_signed() {
+        let kinds = |options: &TokenizerOptions| -> Vec<TokenType> {
+            tokens("-42*+3", options)
+                .map(|token| token.unwrap().kind)
+                .collect()
+        };
+        let signed = TokenizerOptions {
+            signed_numbers: true,
+            ..TokenizerOptions::default()
+        };
+
+        assert_eq!(
+            kinds(&signed),
+            [
+                TokenType::Number(-42),
+                TokenType::Symbol,
+                TokenType::Number(3)
+            ]
+        );
+        assert_eq!(
+            kinds(&TokenizerOptions::default()),
+            [
+                TokenType::Symbol,
+                TokenType::Number(42),
+                TokenType::Symbol,
+                TokenType::Symbol,
+                TokenType::Number(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn longest_operator_wins() {
+        let options = TokenizerOptions::default().with_operators(vec![
+            "*".to_owned(),
+            "**".to_owned(),
+            "->".to_owned(),
+        ]);
+        assert_eq!(
+            positions("1***2->-", &options),
+            Ok(vec![
+                token("1", 1, 1),
+                token("**", 1, 2),
+                token("*", 1, 4),
+                token("2", 1, 5),
+                token("->", 1, 6),
+                token("-", 1, 8),
+            ])
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use advent_of_code_2023::{bench::measure, parsing::parse_all};
+
+use super::{compact, parse_input, Matching};
+
+fn vectors(input: &str) -> usize {
+    let cards = parse_input(input, false).expect("could not parse cards");
+    cards
+        .iter()
+        .map(|card| card.game_win_count(Matching::Set))
+        .sum()
+}
+
+fn bitsets(input: &str) -> usize {
+    parse_all(input, compact::total_win_count).expect("could not parse cards")
+}
+
+/// Checks that both representations agree on every card, then parses `copies` copies of the
+/// cards and counts their matches with each of them.
+pub fn run(input: &str, copies: usize) {
+    let cards = parse_input(input, false).expect("could not parse cards");
+    for (card, line) in cards.iter().zip(input.lines()) {
+        let compact_card = parse_all(line, compact::compact_card).expect("could not parse card");
+        assert_eq!(card.id, compact_card.id);
+        assert_eq!(
+            card.game_win_count(Matching::Set),
+            compact_card.game_win_count(),
+            "card {} matches differently as a bitset",
+            card.id
+        );
+    }
+
+    let large = vec![input.trim_end(); copies].join("\n");
+    println!("{} cards, {} bytes", large.lines().count(), large.len());
+
+    let vectors = measure(|| vectors(&large));
+    let bitsets = measure(|| bitsets(&large));
+    assert_eq!(
+        vectors.checksum, bitsets.checksum,
+        "bitsets counted different matches"
+    );
+    println!("{}", vectors.report("vectors"));
+    println!("{}", bitsets.report("bitsets"));
+}
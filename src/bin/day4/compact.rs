@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use advent_of_code_2023::parsing::number;
+use nom::{
+    character::complete::{line_ending, space0},
+    combinator::{map, opt},
+    multi::fold_many1,
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use super::{card_number, separator};
+
+/// Set of numbers kept as a bitset while they are all below 128, and hashed once one is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberSet {
+    Small(u128),
+    Large(HashSet<usize>),
+}
+
+impl NumberSet {
+    fn new() -> Self {
+        NumberSet::Small(0)
+    }
+
+    fn insert(&mut self, number: usize) {
+        match self {
+            NumberSet::Small(bits) if number < 128 => *bits |= 1 << number,
+            NumberSet::Small(_) => {
+                let mut numbers: HashSet<usize> = self.iter().collect();
+                numbers.insert(number);
+                *self = NumberSet::Large(numbers);
+            }
+            NumberSet::Large(numbers) => {
+                numbers.insert(number);
+            }
+        }
+    }
+
+    fn contains(&self, number: usize) -> bool {
+        match self {
+            NumberSet::Small(bits) => number < 128 && bits & (1 << number) != 0,
+            NumberSet::Large(numbers) => numbers.contains(&number),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            NumberSet::Small(bits) => {
+                Box::new((0..128).filter(move |number| bits & (1 << number) != 0))
+            }
+            NumberSet::Large(numbers) => Box::new(numbers.iter().copied()),
+        }
+    }
+
+    /// Number of numbers in both sets, with a single popcount when both are bitsets.
+    fn intersection_len(&self, other: &NumberSet) -> usize {
+        match (self, other) {
+            (NumberSet::Small(bits), NumberSet::Small(other_bits)) => {
+                (bits & other_bits).count_ones() as usize
+            }
+            _ => self.iter().filter(|number| other.contains(*number)).count(),
+        }
+    }
+}
+
+/// A card reduced to what set matching needs, parsed without allocating as long as its numbers
+/// fit in a bitset.
+#[derive(Debug)]
+pub struct CompactCard {
+    pub id: usize,
+    winning_numbers: NumberSet,
+    game_numbers: NumberSet,
+}
+
+impl CompactCard {
+    /// Same as `Card::game_win_count` with `Matching::Set`.
+    pub fn game_win_count(&self) -> usize {
+        self.winning_numbers.intersection_len(&self.game_numbers)
+    }
+}
+
+fn number_set(i: &str) -> IResult<&str, NumberSet> {
+    fold_many1(
+        preceded(space0, number),
+        NumberSet::new,
+        |mut numbers, number| {
+            numbers.insert(number);
+            numbers
+        },
+    )(i)
+}
+
+pub fn compact_card(i: &str) -> IResult<&str, CompactCard> {
+    let parse_card = tuple((card_number(false), number_set, separator(false), number_set));
+
+    let card = |(id, win, _, game): (usize, NumberSet, &str, NumberSet)| -> CompactCard {
+        CompactCard {
+            id,
+            winning_numbers: win,
+            game_numbers: game,
+        }
+    };
+    map(parse_card, card)(i)
+}
+
+/// Total of the win counts of all the cards, without collecting them. Lines may end in spaces
+/// or tabs, as `parse_input` allows unless strict.
+pub fn total_win_count(i: &str) -> IResult<&str, usize> {
+    fold_many1(
+        terminated(compact_card, pair(space0, opt(line_ending))),
+        || 0,
+        |total, card| total + card.game_win_count(),
+    )(i)
+}
@@ -0,0 +1,728 @@
+mod bench;
+mod compact;
+mod trace;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Range,
+};
+
+use advent_of_code_2023::{
+    input,
+    parsing::{number, number_list, numbered_header, ParseError},
+};
+use nom::{
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{space0, space1},
+    combinator::{all_consuming, map},
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+
+/// Header of a card, "Card 1: ". Unless `strict`, the word may be in any case and the spaces
+/// around the number and the colon may be tabs or missing.
+fn card_number(strict: bool) -> impl FnMut(&str) -> IResult<&str, usize> {
+    move |i| match strict {
+        true => delimited(
+            tuple((tag("Card"), space1)),
+            number,
+            tuple((tag(":"), space1)),
+        )(i),
+        false => numbered_header(tag_no_case("card"))(i),
+    }
+}
+
+/// Separator between the winning numbers and the numbers held, " |", with any spaces or tabs
+/// before it unless `strict`.
+fn separator(strict: bool) -> impl FnMut(&str) -> IResult<&str, &str> {
+    move |i| match strict {
+        true => tag(" |")(i),
+        false => preceded(space0, tag("|"))(i),
+    }
+}
+
+fn card(strict: bool) -> impl FnMut(&str) -> IResult<&str, Card> {
+    move |i| {
+        let parse_card = tuple((
+            card_number(strict),
+            number_list,
+            separator(strict),
+            number_list,
+        ));
+
+        let card = |(id, win, _, game): (usize, Vec<usize>, &str, Vec<usize>)| -> Card {
+            Card {
+                id,
+                winning_numbers: win,
+                game_numbers: game,
+            }
+        };
+        map(parse_card, card)(i)
+    }
+}
+
+/// One card per line, up to trailing whitespace. Unless `strict`, trailing spaces or tabs are
+/// allowed on every line.
+fn parse_input(input: &str, strict: bool) -> Result<Vec<Card>, ParseError> {
+    input
+        .trim_end()
+        .lines()
+        .map(|line| {
+            let parsed = match strict {
+                true => all_consuming(card(strict))(line),
+                false => all_consuming(terminated(card(strict), space0))(line),
+            };
+            parsed
+                .map(|(_, card)| card)
+                .map_err(|err| ParseError::from_nom(input, err))
+        })
+        .collect()
+}
+
+#[derive(Hash, Eq, PartialEq, Debug, Ord, PartialOrd)]
+struct CardId(usize);
+
+#[derive(Debug, Hash, Eq, PartialEq)]
+struct Card {
+    id: usize,
+    winning_numbers: Vec<usize>,
+    game_numbers: Vec<usize>,
+}
+
+/// What counts as a match between the winning numbers and the numbers held.
+#[derive(Debug, Clone, Copy)]
+enum Matching {
+    /// Distinct numbers found in both lists.
+    Set,
+    /// Numbers found in both lists, as many times as they show up in both.
+    Multiset,
+    /// Positions holding the same number in both lists.
+    Positional,
+}
+
+fn counts(numbers: &[usize]) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+    for number in numbers {
+        *counts.entry(*number).or_default() += 1;
+    }
+    counts
+}
+
+#[derive(Debug)]
+struct DuplicateNumber {
+    card_id: usize,
+    line: usize,
+    list: &'static str,
+    number: usize,
+    count: usize,
+}
+
+impl std::fmt::Display for DuplicateNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: card {} lists {} {} times among its {} numbers",
+            self.line, self.card_id, self.number, self.count, self.list
+        )
+    }
+}
+
+impl Card {
+    fn game_win_count(&self, matching: Matching) -> usize {
+        match matching {
+            Matching::Set => {
+                let win_set: HashSet<&usize> = HashSet::from_iter(&self.winning_numbers);
+                let game_set: HashSet<&usize> = HashSet::from_iter(&self.game_numbers);
+
+                win_set.intersection(&game_set).count()
+            }
+            Matching::Multiset => {
+                let game_counts = counts(&self.game_numbers);
+                counts(&self.winning_numbers)
+                    .into_iter()
+                    .map(|(number, count)| count.min(*game_counts.get(&number).unwrap_or(&0)))
+                    .sum()
+            }
+            Matching::Positional => self
+                .winning_numbers
+                .iter()
+                .zip(&self.game_numbers)
+                .filter(|(win, game)| win == game)
+                .count(),
+        }
+    }
+
+    /// Numbers listed more than once among the winning or the held numbers of the card, which
+    /// sits on `line` of the input.
+    fn duplicate_numbers(&self, line: usize) -> Vec<DuplicateNumber> {
+        let mut duplicates = Vec::new();
+
+        for (list, numbers) in [
+            ("winning", &self.winning_numbers),
+            ("held", &self.game_numbers),
+        ] {
+            let mut counts: Vec<(usize, usize)> = counts(numbers).into_iter().collect();
+            counts.sort_unstable();
+            duplicates.extend(counts.into_iter().filter(|(_, count)| *count > 1).map(
+                |(number, count)| DuplicateNumber {
+                    card_id: self.id,
+                    line,
+                    list,
+                    number,
+                    count,
+                },
+            ));
+        }
+
+        duplicates
+    }
+}
+
+/// Points a card is worth for its number of matches, in part 1.
+#[derive(Debug, Clone)]
+enum Scoring {
+    /// 1 point for the first match, doubled for every further match.
+    Exponential,
+    /// 1 point per match.
+    Linear,
+    /// The matches-th Fibonacci number.
+    Fibonacci,
+    /// Points listed by number of matches, starting from none; matches past the end of the
+    /// table score as its last entry.
+    Table(Vec<u128>),
+}
+
+impl Scoring {
+    fn score(&self, matches: usize) -> Option<u128> {
+        match self {
+            Scoring::Exponential => match matches {
+                0 => Some(0),
+                _ => 1u128.checked_shl(matches as u32 - 1),
+            },
+            Scoring::Linear => Some(matches as u128),
+            Scoring::Fibonacci => {
+                let (mut current, mut next) = (0u128, 1u128);
+                for _ in 0..matches {
+                    (current, next) = (next, current.checked_add(next)?);
+                }
+                Some(current)
+            }
+            Scoring::Table(points) => points.get(matches).or(points.last()).copied(),
+        }
+    }
+}
+
+/// Which cards a card wins copies of, and how many, in part 2.
+#[derive(Debug, Clone, Copy)]
+enum CopiesRule {
+    /// One copy of each of the next N cards, N being the number of matches.
+    Next,
+    /// One copy of each of the N cards following the next K ones.
+    SkipThenNext(usize),
+    /// N copies of each of the next N cards.
+    Multiplied,
+}
+
+impl CopiesRule {
+    /// How many cards past the card the won ones start, and how many of them there are.
+    fn won(&self, matches: usize) -> (usize, usize) {
+        match self {
+            CopiesRule::Next | CopiesRule::Multiplied => (0, matches),
+            CopiesRule::SkipThenNext(skip) => (*skip, matches),
+        }
+    }
+
+    /// Copies of each won card handed out by `copies` copies of a card.
+    fn copies_won(&self, copies: u128, matches: usize) -> Option<u128> {
+        match self {
+            CopiesRule::Next | CopiesRule::SkipThenNext(_) => Some(copies),
+            CopiesRule::Multiplied => copies.checked_mul(matches as u128),
+        }
+    }
+}
+
+/// Which cards "the next N cards" won by a card refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cascade {
+    /// Ids must be exactly 1..=N in order, so that positions and ids agree.
+    Contiguous,
+    /// The N cards following it in the input, whatever their ids.
+    ByPosition,
+    /// The cards with the N ids following its own; ids missing from the input are not won.
+    ById,
+}
+
+#[derive(Debug)]
+enum CascadeError {
+    Duplicate {
+        id: usize,
+        line: usize,
+    },
+    OutOfOrder {
+        id: usize,
+        previous: usize,
+        line: usize,
+    },
+    Gap {
+        expected: usize,
+        found: usize,
+        line: usize,
+    },
+    Overflow {
+        line: usize,
+    },
+}
+
+impl std::fmt::Display for CascadeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CascadeError::Duplicate { id, line } => {
+                write!(f, "line {line}: card {id} is listed more than once")
+            }
+            CascadeError::OutOfOrder { id, previous, line } => {
+                write!(f, "line {line}: card {id} comes after card {previous}")
+            }
+            CascadeError::Gap {
+                expected,
+                found,
+                line,
+            } => write!(
+                f,
+                "line {line}: expected card {expected}, found card {found}"
+            ),
+            CascadeError::Overflow { line } => write!(f, "line {line}: too many copies to count"),
+        }
+    }
+}
+
+/// Positions of the cards keyed by id, once their ids are checked against `cascade`.
+fn index_by_id(cards: &[Card], cascade: Cascade) -> Result<BTreeMap<CardId, usize>, CascadeError> {
+    let mut by_id = BTreeMap::new();
+
+    for (index, card) in cards.iter().enumerate() {
+        let line = index + 1;
+        if by_id.insert(CardId(card.id), index).is_some() && cascade != Cascade::ByPosition {
+            return Err(CascadeError::Duplicate { id: card.id, line });
+        }
+        if cascade != Cascade::Contiguous {
+            continue;
+        }
+        if let Some(previous) = index.checked_sub(1).map(|previous| cards[previous].id) {
+            if card.id < previous {
+                return Err(CascadeError::OutOfOrder {
+                    id: card.id,
+                    previous,
+                    line,
+                });
+            }
+        }
+        if card.id != line {
+            return Err(CascadeError::Gap {
+                expected: line,
+                found: card.id,
+                line,
+            });
+        }
+    }
+
+    Ok(by_id)
+}
+
+/// Order in which copies flow through the cards, and which cards each card wins copies of.
+struct CascadePlan {
+    /// Positions of the cards in the input, in the order they are settled.
+    order: Vec<usize>,
+    /// Matches of each card, in input order.
+    matches: Vec<usize>,
+    /// Range of `order` holding the cards won by the card at the same step.
+    won: Vec<Range<usize>>,
+}
+
+impl CascadePlan {
+    fn new(
+        cards: &[Card],
+        matching: Matching,
+        cascade: Cascade,
+        rule: CopiesRule,
+    ) -> Result<Self, CascadeError> {
+        let by_id = index_by_id(cards, cascade)?;
+        let order: Vec<usize> = match cascade {
+            Cascade::Contiguous | Cascade::ByPosition => (0..cards.len()).collect(),
+            Cascade::ById => by_id.values().copied().collect(),
+        };
+        let ids: Vec<usize> = order.iter().map(|&index| cards[index].id).collect();
+        let matches: Vec<usize> = cards
+            .iter()
+            .map(|card| card.game_win_count(matching))
+            .collect();
+
+        let won = order
+            .iter()
+            .enumerate()
+            .map(|(step, &index)| {
+                let (skip, count) = rule.won(matches[index]);
+                // a huge skip or id leaves nothing to win rather than overflowing
+                match cascade {
+                    Cascade::Contiguous | Cascade::ByPosition => {
+                        let first = (step + 1).saturating_add(skip);
+                        first.min(order.len())..first.saturating_add(count).min(order.len())
+                    }
+                    Cascade::ById => {
+                        let last_skipped = ids[step].saturating_add(skip);
+                        ids.partition_point(|&id| id <= last_skipped)
+                            ..ids.partition_point(|&id| id <= last_skipped.saturating_add(count))
+                    }
+                }
+            })
+            .collect();
+
+        Ok(CascadePlan {
+            order,
+            matches,
+            won,
+        })
+    }
+}
+
+/// Number of copies of each card, in input order, once every card won its copies.
+///
+/// The cards are visited in the order copies flow in, which is always forward, and every card
+/// hands out its copies through a difference array: they are added to a running total at the
+/// first card it won and taken back out at the first card past those. This takes O(N + W) steps
+/// for N cards with W winning numbers in total, and O(N log N) when going by id, to find where
+/// each range starts and ends.
+fn cards_copies(plan: &CascadePlan, rule: CopiesRule) -> Result<Vec<u128>, CascadeError> {
+    let steps = plan.order.len();
+    let mut copies = vec![0; steps];
+    let mut starting = vec![0u128; steps + 1];
+    let mut expiring = vec![0u128; steps + 1];
+    let mut running: u128 = 0;
+
+    for (step, &index) in plan.order.iter().enumerate() {
+        let overflow = || CascadeError::Overflow { line: index + 1 };
+        let won = &plan.won[step];
+
+        running = running.checked_add(starting[step]).ok_or_else(overflow)? - expiring[step];
+        let own = running.checked_add(1).ok_or_else(overflow)?;
+        copies[index] = own;
+
+        if won.is_empty() {
+            continue;
+        }
+        let handed_out = rule
+            .copies_won(own, plan.matches[index])
+            .ok_or_else(overflow)?;
+        starting[won.start] = starting[won.start]
+            .checked_add(handed_out)
+            .ok_or_else(overflow)?;
+        expiring[won.end] = expiring[won.end]
+            .checked_add(handed_out)
+            .ok_or_else(overflow)?;
+    }
+
+    Ok(copies)
+}
+
+struct Config {
+    cascade: Cascade,
+    scoring: Scoring,
+    copies: CopiesRule,
+    matching: Matching,
+    validate: bool,
+    trace: Option<trace::Format>,
+    bench: Option<usize>,
+    strict: bool,
+    input: Option<String>,
+}
+
+const USAGE: &str = "usage: day4 [--cascade <contiguous | position | id>] \
+                     [--scoring <exponential | linear | fibonacci | table:<points,...>>] \
+                     [--copies <next | skip:<k> | multiplied>] \
+                     [--matching <set | multiset | positional>] [--validate] \
+                     [--trace <dot | json>] [--bench <copies>] \
+                     [--strict] [--input <path>]";
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut cascade = Cascade::Contiguous;
+    let mut scoring = Scoring::Exponential;
+    let mut copies = CopiesRule::Next;
+    let mut matching = Matching::Set;
+    let mut validate = false;
+    let mut trace = None;
+    let mut bench = None;
+    let mut strict = false;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cascade" => {
+                cascade = match args.next().ok_or("--cascade expects a value")?.as_str() {
+                    "contiguous" => Cascade::Contiguous,
+                    "position" => Cascade::ByPosition,
+                    "id" => Cascade::ById,
+                    other => return Err(format!("unknown cascade: {other}")),
+                }
+            }
+            "--scoring" => {
+                let value = args.next().ok_or("--scoring expects a value")?;
+                scoring = match value.as_str() {
+                    "exponential" => Scoring::Exponential,
+                    "linear" => Scoring::Linear,
+                    "fibonacci" => Scoring::Fibonacci,
+                    other => {
+                        let points = other
+                            .strip_prefix("table:")
+                            .ok_or(format!("unknown scoring: {other}"))?
+                            .split(',')
+                            .map(|points| points.parse::<u128>())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|_| format!("could not parse scoring table: {other}"))?;
+                        Scoring::Table(points)
+                    }
+                }
+            }
+            "--copies" => {
+                let value = args.next().ok_or("--copies expects a value")?;
+                copies = match value.as_str() {
+                    "next" => CopiesRule::Next,
+                    "multiplied" => CopiesRule::Multiplied,
+                    other => {
+                        let skip = other
+                            .strip_prefix("skip:")
+                            .ok_or(format!("unknown copies rule: {other}"))?;
+                        let skip = skip.parse::<usize>().map_err(|_| {
+                            format!("could not parse number of skipped cards: {skip}")
+                        })?;
+                        CopiesRule::SkipThenNext(skip)
+                    }
+                }
+            }
+            "--matching" => {
+                matching = match args.next().ok_or("--matching expects a value")?.as_str() {
+                    "set" => Matching::Set,
+                    "multiset" => Matching::Multiset,
+                    "positional" => Matching::Positional,
+                    other => return Err(format!("unknown matching: {other}")),
+                }
+            }
+            "--validate" => validate = true,
+            "--trace" => {
+                trace = match args.next().ok_or("--trace expects a value")?.as_str() {
+                    "dot" => Some(trace::Format::Dot),
+                    "json" => Some(trace::Format::Json),
+                    other => return Err(format!("unknown trace format: {other}")),
+                }
+            }
+            "--bench" => {
+                let value = args.next().ok_or("--bench expects a value")?;
+                let copies = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("could not parse number of copies: {value}"))?;
+                bench = Some(copies);
+            }
+            "--strict" => strict = true,
+            "--input" => input = Some(args.next().ok_or("--input expects a path")?),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+
+    Ok(Config {
+        cascade,
+        scoring,
+        copies,
+        matching,
+        validate,
+        trace,
+        bench,
+        strict,
+        input,
+    })
+}
+
+const DEBUG: bool = false;
+
+fn main() {
+    let config = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    });
+
+    let input = input::load(
+        config.input.as_deref(),
+        include_str!("../../../inputs/real/day4.txt"),
+    );
+    let input: &str = &input;
+
+    if let Some(copies) = config.bench {
+        bench::run(input, copies);
+        return;
+    }
+
+    let cards = parse_input(input, config.strict).unwrap_or_else(|err| {
+        eprintln!("malformed card at {err}");
+        std::process::exit(1);
+    });
+
+    // debug output goes to stderr, keeping stdout to answers, reports and traces
+    if DEBUG {
+        for card in &cards {
+            eprintln!("{card:?}");
+        }
+    }
+
+    if config.validate {
+        let duplicates = cards
+            .iter()
+            .enumerate()
+            .flat_map(|(index, card)| card.duplicate_numbers(index + 1))
+            .collect::<Vec<_>>();
+        for duplicate in &duplicates {
+            println!("{duplicate}");
+        }
+        println!("{} duplicate numbers", duplicates.len());
+        std::process::exit(if duplicates.is_empty() { 0 } else { 1 });
+    }
+
+    let answer_p1 = cards
+        .iter()
+        .map(|card| {
+            let game_score = card.game_win_count(config.matching);
+
+            if DEBUG {
+                eprintln!("{}: {}", card.id, game_score.saturating_sub(1));
+            }
+
+            config.scoring.score(game_score)
+        })
+        .try_fold(0u128, |total, score| total.checked_add(score?))
+        .unwrap_or_else(|| {
+            eprintln!("score too large to count");
+            std::process::exit(1);
+        });
+
+    let (plan, cards_copies) =
+        CascadePlan::new(&cards, config.matching, config.cascade, config.copies)
+            .and_then(|plan| {
+                let cards_copies = cards_copies(&plan, config.copies)?;
+                Ok((plan, cards_copies))
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("{err}");
+                std::process::exit(1);
+            });
+
+    if let Some(format) = config.trace {
+        let trace = trace::Trace::new(&cards, &plan, &cards_copies, config.copies);
+        print!("{}", trace.render(format));
+        return;
+    }
+
+    let answer_p2 = cards_copies
+        .iter()
+        .try_fold(0u128, |total, copies| total.checked_add(*copies))
+        .unwrap_or_else(|| {
+            eprintln!("too many copies to count");
+            std::process::exit(1);
+        });
+
+    println!("{answer_p1}");
+    println!("{answer_p2}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../../inputs/example/day4.txt");
+    const REAL: &str = include_str!("../../../inputs/real/day4.txt");
+
+    /// The cascade as first written: every card hands its copies to each won card in turn.
+    fn naive_copies(cards: &[Card], rule: CopiesRule) -> Vec<u128> {
+        let mut copies = vec![1u128; cards.len()];
+
+        for index in 0..cards.len() {
+            let matches = cards[index].game_win_count(Matching::Set);
+            let (skip, count) = rule.won(matches);
+            let handed_out = rule.copies_won(copies[index], matches).unwrap();
+            for won in
+                (index + 1 + skip..index + 1 + skip + count).take_while(|&won| won < cards.len())
+            {
+                copies[won] += handed_out;
+            }
+        }
+
+        copies
+    }
+
+    fn copies(
+        cards: &[Card],
+        cascade: Cascade,
+        rule: CopiesRule,
+    ) -> Result<Vec<u128>, CascadeError> {
+        let plan = CascadePlan::new(cards, Matching::Set, cascade, rule)?;
+        cards_copies(&plan, rule)
+    }
+
+    #[test]
+    fn example_copies() {
+        let cards = parse_input(EXAMPLE, true).unwrap();
+        let next = copies(&cards, Cascade::Contiguous, CopiesRule::Next).unwrap();
+        assert_eq!(next, [1, 2, 4, 8, 14, 1]);
+
+        let skip = copies(&cards, Cascade::Contiguous, CopiesRule::SkipThenNext(1)).unwrap();
+        assert_eq!(skip.iter().sum::<u128>(), 19);
+
+        let multiplied = copies(&cards, Cascade::Contiguous, CopiesRule::Multiplied).unwrap();
+        assert_eq!(multiplied.iter().sum::<u128>(), 147);
+    }
+
+    #[test]
+    fn agrees_with_the_naive_cascade() {
+        let rules = [
+            CopiesRule::Next,
+            CopiesRule::SkipThenNext(1),
+            CopiesRule::SkipThenNext(3),
+            CopiesRule::Multiplied,
+        ];
+
+        for input in [EXAMPLE, REAL] {
+            let cards = parse_input(input, true).unwrap();
+            for rule in rules {
+                let expected = naive_copies(&cards, rule);
+                assert_eq!(copies(&cards, Cascade::ByPosition, rule).unwrap(), expected);
+                assert_eq!(copies(&cards, Cascade::ById, rule).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn by_id_follows_ids_rather_than_lines() {
+        let reversed: Vec<&str> = EXAMPLE.lines().rev().collect();
+        let cards = parse_input(&reversed.join("\n"), true).unwrap();
+
+        let by_id = copies(&cards, Cascade::ById, CopiesRule::Next).unwrap();
+        assert_eq!(by_id, [1, 14, 8, 4, 2, 1]);
+        assert!(matches!(
+            copies(&cards, Cascade::Contiguous, CopiesRule::Next),
+            Err(CascadeError::Gap {
+                expected: 1,
+                found: 6,
+                line: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_the_card_that_overflows() {
+        let input: String = (1..=200)
+            .map(|id| format!("Card {id}: 1 2 3 | 1 2 3\n"))
+            .collect();
+        let cards = parse_input(&input, true).unwrap();
+
+        assert!(matches!(
+            copies(&cards, Cascade::Contiguous, CopiesRule::Next),
+            Err(CascadeError::Overflow { line: 146 })
+        ));
+    }
+}
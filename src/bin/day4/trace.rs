@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use super::{Card, CascadePlan, CopiesRule};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Dot,
+    Json,
+}
+
+/// Copies of a card won through another card, all of its copies included.
+struct Contribution {
+    from: usize,
+    to: usize,
+    copies: u128,
+}
+
+/// Where the copies of every card came from. Cards are referred to by position, since ids need
+/// not be unique when going by position.
+pub struct Trace<'a> {
+    cards: &'a [Card],
+    matches: &'a [usize],
+    copies: &'a [u128],
+    contributions: Vec<Contribution>,
+}
+
+impl<'a> Trace<'a> {
+    pub fn new(
+        cards: &'a [Card],
+        plan: &'a CascadePlan,
+        copies: &'a [u128],
+        rule: CopiesRule,
+    ) -> Self {
+        let mut contributions = Vec::new();
+
+        for (step, &from) in plan.order.iter().enumerate() {
+            // the cascade went through without overflowing, so neither does this
+            let handed_out = rule
+                .copies_won(copies[from], plan.matches[from])
+                .expect("copies were counted already");
+            contributions.extend(plan.order[plan.won[step].clone()].iter().map(|&to| {
+                Contribution {
+                    from,
+                    to,
+                    copies: handed_out,
+                }
+            }));
+        }
+
+        Trace {
+            cards,
+            matches: &plan.matches,
+            copies,
+            contributions,
+        }
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Dot => self.to_dot(),
+            Format::Json => self.to_json(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph cascade {\n    node [shape=box];\n");
+
+        for (index, card) in self.cards.iter().enumerate() {
+            writeln!(
+                dot,
+                "    card{index} [label=\"Card {}\\n{} matches\\n{} copies\"];",
+                card.id, self.matches[index], self.copies[index]
+            )
+            .unwrap();
+        }
+        for contribution in &self.contributions {
+            writeln!(
+                dot,
+                "    card{} -> card{} [label=\"{}\"];",
+                contribution.from, contribution.to, contribution.copies
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = String::from("[\n");
+
+        for (index, card) in self.cards.iter().enumerate() {
+            let contributors = self
+                .contributions
+                .iter()
+                .filter(|contribution| contribution.to == index)
+                .map(|contribution| {
+                    format!(
+                        "{{\"line\": {}, \"id\": {}, \"copies\": {}}}",
+                        contribution.from + 1,
+                        self.cards[contribution.from].id,
+                        contribution.copies
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let separator = if index + 1 < self.cards.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                json,
+                "  {{\"line\": {}, \"id\": {}, \"matches\": {}, \"copies\": {}, \
+                 \"won_from\": [{contributors}]}}{separator}",
+                index + 1,
+                card.id,
+                self.matches[index],
+                self.copies[index]
+            )
+            .unwrap();
+        }
+
+        json.push_str("]\n");
+        json
+    }
+}
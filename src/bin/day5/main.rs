@@ -2,14 +2,19 @@ mod index_tree;
 mod parsing;
 mod types;
 
+use advent_of_code_2023::input;
 use parsing::parse_input;
 use types::*;
 
 const DEBUG: bool = true;
 fn main() {
-    let input = include_str!("../../../inputs/real/day5.txt");
+    let input = input::from_args("day5", include_str!("../../../inputs/real/day5.txt"));
+    let input: &str = &input;
 
-    let (_, (seeds, maps)) = parse_input(input).unwrap();
+    let (seeds, maps) = parse_input(input).unwrap_or_else(|err| {
+        eprintln!("malformed almanac at {err}");
+        std::process::exit(1);
+    });
 
     println!("{seeds:?}");
     if DEBUG {
@@ -29,9 +34,9 @@ fn main() {
     let merged = seeds_ranges.remove_overlapping();
     let merged_count = merged.0.iter().map(|r| r.len()).sum::<usize>();
     dbg!(&locations);
-    println!("{0}", answer_p1.value);
     println!("seeds count: {seeds_count}");
     println!("after merging overlaps: {merged_count}");
+    println!("{0}", answer_p1.value);
 }
 
 fn process_seeds(seeds: Seeds) -> Vec<usize> {
@@ -1,48 +1,35 @@
 use std::str::FromStr;
 
+use advent_of_code_2023::parsing::{
+    blank_lines, labelled, number, number_list, parse_all, sections, ParseError,
+};
 use nom::{
     bytes::complete::{tag, take_while1},
     character::{
-        complete::{alpha1, anychar, digit1, newline, space1},
+        complete::{alpha1, anychar, newline, space1},
         is_alphabetic,
     },
     combinator::map,
     multi::{many_till, separated_list1},
-    sequence::{delimited, separated_pair, tuple},
+    sequence::{preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
 use crate::types::*;
 
 fn seeds(i: &str) -> IResult<&str, Seeds> {
-    let parse_seeds = delimited(
-        tuple((tag("seeds:"), space1)),
-        separated_list1(space1, digit1),
-        tuple((newline, newline)),
-    );
-
-    let seeds = |s: Vec<&str>| -> Seeds {
-        Seeds(
-            s.iter()
-                .map(|seed| seed.parse::<usize>().unwrap())
-                .collect(),
-        )
-    };
+    let parse_seeds = terminated(labelled("seeds", number_list), blank_lines);
 
-    map(parse_seeds, seeds)(i)
+    map(parse_seeds, Seeds)(i)
 }
 
 fn remap(i: &str) -> IResult<&str, MapDetails> {
-    let parse_remap = separated_list1(space1, digit1);
-    let remap = |s: Vec<&str>| -> MapDetails {
-        let parsed = s
-            .iter()
-            .map(|snum| snum.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+    let parse_remap = tuple((number, preceded(space1, number), preceded(space1, number)));
+    let remap = |(dest_start, source_start, length)| -> MapDetails {
         MapDetails {
-            dest_start: parsed[0],
-            source_start: parsed[1],
-            length: parsed[2],
+            dest_start,
+            source_start,
+            length,
         }
     };
 
@@ -77,7 +64,7 @@ fn a_to_b_map(i: &str) -> IResult<&str, FarmingMap> {
 }
 
 fn maps(i: &str) -> IResult<&str, Vec<FarmingMap>> {
-    separated_list1(tuple((newline, newline)), a_to_b_map)(i)
+    sections(a_to_b_map)(i)
     // let maps = |undefined_maps: Vec<FarmingMap>| -> Vec<FarmingMap> {
     //     undefined_maps
     //         .into_iter()
@@ -121,6 +108,6 @@ fn maps(i: &str) -> IResult<&str, Vec<FarmingMap>> {
     // map(parse_maps, maps)(i)
 }
 
-pub fn parse_input(i: &str) -> IResult<&str, (Seeds, Vec<FarmingMap>)> {
-    tuple((seeds, maps))(i)
+pub fn parse_input(i: &str) -> Result<(Seeds, Vec<FarmingMap>), ParseError> {
+    parse_all(i, tuple((seeds, maps)))
 }
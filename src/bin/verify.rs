@@ -0,0 +1,236 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Parts each day solves, in the order it prints their answers as the last lines of its output.
+const PARTS: &[(&str, &[u32])] = &[
+    ("day1", &[2]),
+    ("day2", &[1, 2]),
+    ("day3", &[1, 2]),
+    ("day4", &[1, 2]),
+    ("day5", &[1]),
+];
+
+/// Answers recorded for a day given one input.
+struct Expected {
+    day: String,
+    input_name: String,
+    input: PathBuf,
+    answers: BTreeMap<u32, String>,
+}
+
+struct MalformedAnswers {
+    line: usize,
+    reason: String,
+}
+
+impl std::fmt::Display for MalformedAnswers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "malformed answers at line {}: {}",
+            self.line, self.reason
+        )
+    }
+}
+
+/// Reads the small subset of TOML the answers are written in: `[day.input]` tables holding
+/// `partN` keys with integer or string values, an optional `input` path, and whole-line
+/// comments.
+fn parse_answers(text: &str, root: &Path) -> Result<Vec<Expected>, MalformedAnswers> {
+    let mut expected: Vec<Expected> = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let malformed = |reason: String| MalformedAnswers {
+            line: index + 1,
+            reason,
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(table) = line.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            let (day, input_name) = table
+                .split_once('.')
+                .ok_or_else(|| malformed(format!("expected [day.input], found [{table}]")))?;
+            expected.push(Expected {
+                day: day.to_owned(),
+                input_name: input_name.to_owned(),
+                input: root
+                    .join("inputs")
+                    .join(input_name)
+                    .join(format!("{day}.txt")),
+                answers: BTreeMap::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| malformed(format!("expected key = value, found {line}")))?;
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(string) => string,
+            None if !value.is_empty() && value.chars().all(|ch| ch.is_ascii_digit()) => value,
+            None => return Err(malformed(format!("could not parse value: {value}"))),
+        };
+        let current = expected
+            .last_mut()
+            .ok_or_else(|| malformed(format!("{key} is set outside of any table")))?;
+
+        match key {
+            "input" => current.input = root.join(value),
+            _ => {
+                let part = key
+                    .strip_prefix("part")
+                    .and_then(|part| part.parse::<u32>().ok())
+                    .ok_or_else(|| malformed(format!("unknown key: {key}")))?;
+                current.answers.insert(part, value.to_owned());
+            }
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Rebuilds the days with the profile of the `binaries` directory they are run from, so that
+/// binaries older than their sources are never checked.
+fn build(root: &Path, binaries: &Path) -> Result<(), String> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command
+        .current_dir(root)
+        .args(["build", "--quiet", "--bins"]);
+    match binaries.file_name().and_then(|profile| profile.to_str()) {
+        Some("debug") | None => {}
+        Some(profile) => {
+            command.args(["--profile", profile]);
+        }
+    }
+
+    let status = command
+        .status()
+        .map_err(|err| format!("could not run cargo: {err}"))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("cargo build exited with {status}")),
+    }
+}
+
+/// Runs the day on its input and returns the answers it printed, keyed by part.
+fn run(binaries: &Path, expected: &Expected) -> Result<(BTreeMap<u32, String>, Duration), String> {
+    let parts = PARTS
+        .iter()
+        .find(|(day, _)| *day == expected.day)
+        .map(|(_, parts)| *parts)
+        .ok_or(format!("unknown day: {}", expected.day))?;
+    let binary = binaries.join(&expected.day);
+    if !binary.exists() {
+        return Err(format!("{} was not built", expected.day));
+    }
+
+    let start = Instant::now();
+    let output = Command::new(&binary)
+        .arg("--input")
+        .arg(&expected.input)
+        .output()
+        .map_err(|err| format!("could not run {}: {err}", expected.day))?;
+    let elapsed = start.elapsed();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    let printed = &lines[lines.len().saturating_sub(parts.len())..];
+    let answers = parts
+        .iter()
+        .zip(printed)
+        .map(|(part, answer)| (*part, answer.trim().to_owned()))
+        .collect();
+
+    Ok((answers, elapsed))
+}
+
+const USAGE: &str = "usage: verify [<day>...]";
+
+fn main() {
+    let days: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(arg) = days.iter().find(|day| day.starts_with('-')) {
+        eprintln!("unknown argument: {arg}");
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    }
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let path = root.join("inputs").join("answers.toml");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("could not read {}: {err}", path.display());
+        std::process::exit(1);
+    });
+    let expected = parse_answers(&text, root).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    // the days are built next to this binary
+    let binaries = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .expect("could not locate the built binaries");
+    build(root, &binaries).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    let (mut passed, mut failed) = (0, 0);
+
+    for expected in expected
+        .iter()
+        .filter(|expected| days.is_empty() || days.contains(&expected.day))
+    {
+        let name = format!("{} {}", expected.day, expected.input_name);
+        match run(&binaries, expected) {
+            Ok((answers, elapsed)) => {
+                for (part, answer) in &expected.answers {
+                    match answers.get(part) {
+                        Some(found) if found == answer => {
+                            passed += 1;
+                            println!("pass {name} part {part}: {answer} ({elapsed:.2?})");
+                        }
+                        Some(found) => {
+                            failed += 1;
+                            println!(
+                                "FAIL {name} part {part}: expected {answer}, found {found} \
+                                 ({elapsed:.2?})"
+                            );
+                        }
+                        None => {
+                            failed += 1;
+                            println!("FAIL {name} part {part}: no answer printed");
+                        }
+                    }
+                }
+            }
+            Err(err) => {
+                failed += expected.answers.len();
+                println!("FAIL {name}: {err}");
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
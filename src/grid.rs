@@ -0,0 +1,379 @@
+use std::ops::{Index, IndexMut, Range};
+
+/// Position of a cell, counted from the top left corner of the grid.
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Location {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(row: usize, column: usize) -> Self {
+        Location { row, column }
+    }
+
+    /// Moves by the given deltas, or `None` when that would leave the non-negative quadrant.
+    pub fn offset(&self, row_delta: isize, column_delta: isize) -> Option<Location> {
+        Some(Location {
+            row: self.row.checked_add_signed(row_delta)?,
+            column: self.column.checked_add_signed(column_delta)?,
+        })
+    }
+}
+
+/// Which cells count as touching a given cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Orthogonal neighbors only.
+    Four,
+    /// Orthogonal and diagonal neighbors.
+    Eight,
+}
+
+/// Cells within `radius` steps of a given cell, where a step is an orthogonal move for
+/// [`Connectivity::Four`] and any king move for [`Connectivity::Eight`]. With `wrap` set, the grid
+/// is treated as a torus and neighborhoods reaching over an edge continue on the opposite side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighborhood {
+    pub connectivity: Connectivity,
+    pub radius: usize,
+    pub wrap: bool,
+}
+
+impl Neighborhood {
+    pub fn moore(radius: usize) -> Self {
+        Neighborhood {
+            connectivity: Connectivity::Eight,
+            radius,
+            wrap: false,
+        }
+    }
+
+    /// How many columns the neighborhood reaches to either side on the row `row_delta` rows
+    /// away from the center, or `None` when it does not reach that row at all.
+    pub fn reach(&self, row_delta: isize) -> Option<usize> {
+        let distance = row_delta.unsigned_abs();
+        match self.connectivity {
+            Connectivity::Four => self.radius.checked_sub(distance),
+            Connectivity::Eight => (distance <= self.radius).then_some(self.radius),
+        }
+    }
+
+    /// Row `row_delta` rows away from `row` on a grid `height` rows tall. Wrapping neighborhoods
+    /// continue on the opposite edge, others give `None` past either edge.
+    fn row(&self, row: usize, row_delta: isize, height: usize) -> Option<usize> {
+        if !self.wrap {
+            return row
+                .checked_add_signed(row_delta)
+                .filter(|&row| row < height);
+        }
+        if height == 0 {
+            return None;
+        }
+        Some((row as isize + row_delta).rem_euclid(height as isize) as usize)
+    }
+
+    /// Columns `lowest..=highest` of a row `row_len` cells long, as at most two ranges with the
+    /// unused one left empty. Without wrap the span is clipped to the row. With wrap it continues
+    /// on the other end of the row, and a span as long as the row covers all of it exactly once.
+    fn columns(&self, lowest: isize, highest: isize, row_len: usize) -> [Range<usize>; 2] {
+        let row_len = row_len as isize;
+
+        if !self.wrap {
+            let start = lowest.clamp(0, row_len) as usize;
+            let end = (highest + 1).clamp(0, row_len) as usize;
+            return [start..end.max(start), 0..0];
+        }
+        if row_len == 0 {
+            return [0..0, 0..0];
+        }
+        if highest - lowest + 1 >= row_len {
+            return [0..row_len as usize, 0..0];
+        }
+
+        let (start, end) = (lowest.rem_euclid(row_len), highest.rem_euclid(row_len));
+        if start <= end {
+            [start as usize..end as usize + 1, 0..0]
+        } else {
+            [start as usize..row_len as usize, 0..end as usize + 1]
+        }
+    }
+
+    /// Cells in the neighborhood of the `len` cells starting at `location`, those cells included,
+    /// as column ranges of every row reached. Rows are `height` deep and `row_len` cells long. A
+    /// wrapping neighborhood taller than the grid reaches some rows more than once.
+    pub fn around(
+        self,
+        location: Location,
+        len: usize,
+        height: usize,
+        row_len: impl Fn(usize) -> usize,
+    ) -> impl Iterator<Item = (usize, [Range<usize>; 2])> {
+        let radius = self.radius as isize;
+
+        (-radius..=radius).filter_map(move |row_delta| {
+            let reach = self.reach(row_delta)? as isize;
+            let row = self.row(location.row, row_delta, height)?;
+            let lowest = location.column as isize - reach;
+            let highest = (location.column + len) as isize - 1 + reach;
+            Some((row, self.columns(lowest, highest, row_len(row))))
+        })
+    }
+}
+
+/// Rows of cells addressed by `(row, column)`. Rows may differ in length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    rows: Vec<Vec<T>>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from text, one row per line, mapping every character through `cell`.
+    /// Both `\n` and `\r\n` line endings are accepted.
+    pub fn parse_with(text: &str, cell: impl Fn(char) -> T) -> Self {
+        Grid {
+            rows: text
+                .lines()
+                .map(|line| line.chars().map(&cell).collect())
+                .collect(),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Length of the given row, zero for rows past the bottom of the grid.
+    pub fn row_len(&self, row: usize) -> usize {
+        self.rows.get(row).map_or(0, Vec::len)
+    }
+
+    /// Length of the longest row.
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or_default()
+    }
+
+    pub fn contains(&self, location: Location) -> bool {
+        location.column < self.row_len(location.row)
+    }
+
+    pub fn get(&self, location: Location) -> Option<&T> {
+        self.rows.get(location.row)?.get(location.column)
+    }
+
+    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> {
+        self.rows.get_mut(location.row)?.get_mut(location.column)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.rows.iter().map(Vec::as_slice)
+    }
+
+    /// Locations in the `neighborhood` of `location` that lie inside the grid. On a wrapping
+    /// neighborhood, rows wrap around the height of the grid and columns around the length of
+    /// the row they land on; a location may then be reached from more than one row. The center
+    /// is never its own neighbor.
+    pub fn neighborhood(
+        &self,
+        location: Location,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = Location> + '_ {
+        neighborhood
+            .around(location, 1, self.height(), |row| self.row_len(row))
+            .flat_map(|(row, ranges)| {
+                ranges
+                    .into_iter()
+                    .flatten()
+                    .map(move |column| Location { row, column })
+            })
+            .filter(move |neighbor| *neighbor != location)
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self
+                .rows
+                .iter()
+                .map(|cells| cells.iter().map(&f).collect())
+                .collect(),
+        }
+    }
+}
+
+impl<T> Index<Location> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, location: Location) -> &T {
+        &self.rows[location.row][location.column]
+    }
+}
+
+impl<T> IndexMut<Location> for Grid<T> {
+    fn index_mut(&mut self, location: Location) -> &mut T {
+        &mut self.rows[location.row][location.column]
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with(text, |ch| ch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn von_neumann(radius: usize) -> Neighborhood {
+        Neighborhood {
+            connectivity: Connectivity::Four,
+            ..Neighborhood::moore(radius)
+        }
+    }
+
+    fn wrapping(neighborhood: Neighborhood) -> Neighborhood {
+        Neighborhood {
+            wrap: true,
+            ..neighborhood
+        }
+    }
+
+    fn locations(pairs: &[(usize, usize)]) -> BTreeSet<Location> {
+        pairs
+            .iter()
+            .map(|&(row, column)| Location::new(row, column))
+            .collect()
+    }
+
+    #[test]
+    fn offset_stays_in_the_non_negative_quadrant() {
+        assert_eq!(Location::new(2, 3).offset(-2, 1), Some(Location::new(0, 4)));
+        assert_eq!(Location::new(0, 0).offset(-1, 0), None);
+        assert_eq!(Location::new(0, 0).offset(0, -1), None);
+        assert_eq!(Location::new(0, usize::MAX).offset(0, 1), None);
+    }
+
+    #[test]
+    fn reach_shrinks_with_distance_only_for_von_neumann() {
+        let moore = Neighborhood::moore(2);
+        assert_eq!(moore.reach(0), Some(2));
+        assert_eq!(moore.reach(-2), Some(2));
+        assert_eq!(moore.reach(3), None);
+
+        let von_neumann = von_neumann(2);
+        assert_eq!(von_neumann.reach(0), Some(2));
+        assert_eq!(von_neumann.reach(1), Some(1));
+        assert_eq!(von_neumann.reach(-2), Some(0));
+        assert_eq!(von_neumann.reach(3), None);
+    }
+
+    #[test]
+    fn contains_follows_ragged_rows() {
+        let grid = Grid::parse("abc\nd\nefgh");
+        assert!(grid.contains(Location::new(0, 2)));
+        assert!(grid.contains(Location::new(1, 0)));
+        assert!(!grid.contains(Location::new(1, 1)));
+        assert!(grid.contains(Location::new(2, 3)));
+        assert!(!grid.contains(Location::new(3, 0)));
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.row_len(3), 0);
+    }
+
+    #[test]
+    fn neighborhood_skips_cells_outside_ragged_rows() {
+        let grid = Grid::parse("abc\nd\nefgh");
+        let around = |row, column| -> BTreeSet<Location> {
+            grid.neighborhood(Location::new(row, column), Neighborhood::moore(1))
+                .collect()
+        };
+
+        assert_eq!(around(1, 0), locations(&[(0, 0), (0, 1), (2, 0), (2, 1)]));
+        assert_eq!(around(0, 2), locations(&[(0, 1)]));
+        assert_eq!(around(2, 3), locations(&[(2, 2)]));
+    }
+
+    #[test]
+    fn wrapping_neighborhood_skips_empty_rows() {
+        let grid = Grid::parse("ab\n\ncd");
+        let around: BTreeSet<Location> = grid
+            .neighborhood(Location::new(0, 0), wrapping(Neighborhood::moore(1)))
+            .collect();
+        assert_eq!(around, locations(&[(0, 1), (2, 0), (2, 1)]));
+
+        let empty = Grid::parse("");
+        let wrapping = wrapping(Neighborhood::moore(1));
+        assert_eq!(empty.neighborhood(Location::new(0, 0), wrapping).count(), 0);
+    }
+
+    #[test]
+    fn rows_wrap_only_when_asked() {
+        let clipped = Neighborhood::moore(1);
+        assert_eq!(clipped.row(0, -1, 3), None);
+        assert_eq!(clipped.row(2, 1, 3), None);
+        assert_eq!(clipped.row(1, 1, 3), Some(2));
+
+        let wrapping = wrapping(clipped);
+        assert_eq!(wrapping.row(0, -1, 3), Some(2));
+        assert_eq!(wrapping.row(2, 1, 3), Some(0));
+        assert_eq!(wrapping.row(0, -4, 3), Some(2));
+        assert_eq!(wrapping.row(0, 1, 0), None);
+    }
+
+    #[test]
+    fn around_lists_column_ranges_by_row() {
+        let row_lens = [5, 0, 3];
+        let around = |neighborhood: Neighborhood, row, column, len| -> Vec<_> {
+            neighborhood
+                .around(Location::new(row, column), len, row_lens.len(), |row| {
+                    row_lens[row]
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            around(Neighborhood::moore(1), 0, 1, 2),
+            [(0, [0..4, 0..0]), (1, [0..0, 0..0])]
+        );
+        assert_eq!(
+            around(wrapping(von_neumann(1)), 0, 0, 1),
+            [(2, [0..1, 0..0]), (0, [4..5, 0..2]), (1, [0..0, 0..0])]
+        );
+    }
+
+    #[test]
+    fn columns_are_clipped_without_wrap() {
+        let clipped = Neighborhood::moore(1);
+        assert_eq!(clipped.columns(-2, 1, 5), [0..2, 0..0]);
+        assert_eq!(clipped.columns(3, 6, 5), [3..5, 0..0]);
+        assert_eq!(clipped.columns(6, 8, 5), [5..5, 0..0]);
+        assert_eq!(clipped.columns(-1, 1, 0), [0..0, 0..0]);
+    }
+
+    #[test]
+    fn columns_wrap_around_the_row() {
+        let wrapping = wrapping(Neighborhood::moore(1));
+        // no edge crossed
+        assert_eq!(wrapping.columns(1, 3, 5), [1..4, 0..0]);
+        // split over the left edge, from a negative lowest column
+        assert_eq!(wrapping.columns(-2, 1, 5), [3..5, 0..2]);
+        // split over the right edge
+        assert_eq!(wrapping.columns(3, 6, 5), [3..5, 0..2]);
+        // past the right edge altogether
+        assert_eq!(wrapping.columns(6, 7, 5), [1..3, 0..0]);
+        // a span as long as the row or longer covers it once
+        assert_eq!(wrapping.columns(-1, 3, 5), [0..5, 0..0]);
+        assert_eq!(wrapping.columns(-3, 3, 5), [0..5, 0..0]);
+        assert_eq!(wrapping.columns(-1, 1, 0), [0..0, 0..0]);
+    }
+
+    #[test]
+    fn parse_with_accepts_crlf() {
+        let grid = Grid::parse_with("#.\r\n.#\r\n", |ch| ch == '#');
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.row_len(0), 2);
+        assert!(grid[Location::new(1, 1)]);
+        assert!(!grid[Location::new(1, 0)]);
+    }
+}
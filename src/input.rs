@@ -0,0 +1,42 @@
+use std::borrow::Cow;
+
+/// Puzzle input read from `path`, or the `embedded` one when no path is given. Exits when the
+/// file cannot be read.
+pub fn load(path: Option<&str>, embedded: &'static str) -> Cow<'static, str> {
+    match path {
+        Some(path) => std::fs::read_to_string(path)
+            .map(Cow::Owned)
+            .unwrap_or_else(|err| {
+                eprintln!("could not read {path}: {err}");
+                std::process::exit(1);
+            }),
+        None => Cow::Borrowed(embedded),
+    }
+}
+
+/// Path given with `--input <path>`, the only argument taken by days without options of their
+/// own.
+fn input_arg(mut args: impl Iterator<Item = String>) -> Result<Option<String>, String> {
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.next().ok_or("--input expects a path")?),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+
+    Ok(input)
+}
+
+/// Puzzle input of a `day` taking no argument but `--input <path>`, loaded as with [`load`].
+/// Exits with its usage on any other argument.
+pub fn from_args(day: &str, embedded: &'static str) -> Cow<'static, str> {
+    let path = input_arg(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!("usage: {day} [--input <path>]");
+        std::process::exit(2);
+    });
+
+    load(path.as_deref(), embedded)
+}
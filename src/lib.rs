@@ -0,0 +1,4 @@
+pub mod bench;
+pub mod grid;
+pub mod input;
+pub mod parsing;
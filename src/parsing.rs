@@ -0,0 +1,258 @@
+use std::{fmt, ops::RangeFrom};
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{i64, line_ending, space0, space1, u64},
+    combinator::{map_res, value},
+    error::Error,
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, preceded, tuple},
+    AsChar, Compare, IResult, InputIter, InputLength, InputTake, Parser, Slice,
+};
+
+/// Unsigned integer, failing when it does not fit in `T`.
+pub fn number<I, T>(i: I) -> IResult<I, T>
+where
+    I: Clone + InputIter + InputLength + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    T: TryFrom<u64>,
+{
+    map_res(u64, T::try_from)(i)
+}
+
+/// Integer with an optional `+` or `-` sign, failing when it does not fit in `T`.
+pub fn signed_number<I, T>(i: I) -> IResult<I, T>
+where
+    I: Clone + InputIter + InputLength + InputTake + Slice<RangeFrom<usize>>,
+    I: for<'a> Compare<&'a [u8]>,
+    <I as InputIter>::Item: AsChar,
+    T: TryFrom<i64>,
+{
+    map_res(i64, T::try_from)(i)
+}
+
+/// Unsigned integers separated by spaces or tabs, leading ones included.
+pub fn number_list<T: TryFrom<u64>>(i: &str) -> IResult<&str, Vec<T>> {
+    preceded(space0, separated_list1(space1, number))(i)
+}
+
+/// Numbered header such as "Game 12: ", returning the number. Spaces or tabs may be missing
+/// between the parts, but not the colon.
+pub fn numbered_header<'a, T, L>(label: L) -> impl FnMut(&'a str) -> IResult<&'a str, T>
+where
+    T: TryFrom<u64>,
+    L: Parser<&'a str, &'a str, Error<&'a str>>,
+{
+    delimited(
+        pair(label, space0),
+        number,
+        tuple((space0, tag(":"), space0)),
+    )
+}
+
+/// Value introduced by a label and a colon, such as "seeds: 79 14 55 13".
+pub fn labelled<'a, O, P>(label: &'a str, value: P) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    P: Parser<&'a str, O, Error<&'a str>>,
+{
+    preceded(tuple((tag(label), tag(":"), space0)), value)
+}
+
+/// End of a line followed by at least one blank line, which may hold spaces or tabs.
+pub fn blank_lines(i: &str) -> IResult<&str, ()> {
+    value((), pair(line_ending, many1(pair(space0, line_ending))))(i)
+}
+
+/// Sections separated by blank lines.
+pub fn sections<'a, O, P>(section: P) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where
+    P: Parser<&'a str, O, Error<&'a str>>,
+{
+    separated_list1(blank_lines, section)
+}
+
+/// Characters of the offending line shown in errors, from where parsing stopped.
+const SNIPPET_LEN: usize = 24;
+
+/// Where parsing stopped, counted in lines and characters from the start of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub found: Option<char>,
+    /// The rest of the line from where parsing stopped, cut to `SNIPPET_LEN` characters.
+    pub snippet: String,
+    /// Whether the parser succeeded but left more than whitespace behind.
+    pub unparsed: bool,
+}
+
+impl ParseError {
+    /// Error at the start of `rest`, a slice of `input`. A `rest` taken from anywhere else is
+    /// placed by its length, counting from the end of `input`.
+    pub fn at(input: &str, rest: &str) -> Self {
+        let offset = (rest.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| input.is_char_boundary(offset))
+            .or_else(|| {
+                let offset = input.len().checked_sub(rest.len())?;
+                input.is_char_boundary(offset).then_some(offset)
+            })
+            .unwrap_or(input.len());
+        let consumed = &input[..offset];
+        let line_start = consumed.rfind('\n').map_or(0, |newline| newline + 1);
+
+        let rest_of_line = input[offset..].lines().next().unwrap_or_default();
+
+        ParseError {
+            line: consumed.matches('\n').count() + 1,
+            column: consumed[line_start..].chars().count() + 1,
+            found: input[offset..].chars().next(),
+            snippet: rest_of_line.chars().take(SNIPPET_LEN).collect(),
+            unparsed: false,
+        }
+    }
+
+    /// Error for the input left over at `rest`, pointing past any whitespace leading it.
+    pub fn unparsed(input: &str, rest: &str) -> Self {
+        ParseError {
+            unparsed: true,
+            ..ParseError::at(input, rest.trim_start())
+        }
+    }
+
+    /// Locates in `input` the error a parser of `input`, or of a slice of it, returned.
+    pub fn from_nom(input: &str, err: nom::Err<Error<&str>>) -> Self {
+        match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => ParseError::at(input, err.input),
+            nom::Err::Incomplete(_) => ParseError::at(input, &input[input.len()..]),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.found {
+            _ if self.unparsed => return write!(f, "unparsed input {:?}", self.snippet),
+            Some('\n') | Some('\r') => write!(f, "unexpected end of line")?,
+            Some(found) => write!(f, "unexpected {found:?}")?,
+            None => write!(f, "unexpected end of input")?,
+        }
+        match self.snippet.is_empty() {
+            true => Ok(()),
+            false => write!(f, " in {:?}", self.snippet),
+        }
+    }
+}
+
+/// Runs `parser` over `input`, which it must consume entirely but for trailing whitespace.
+pub fn parse_all<'a, O, P>(input: &'a str, mut parser: P) -> Result<O, ParseError>
+where
+    P: Parser<&'a str, O, Error<&'a str>>,
+{
+    let (rest, output) = parser
+        .parse(input)
+        .map_err(|err| ParseError::from_nom(input, err))?;
+
+    match rest.trim().is_empty() {
+        true => Ok(output),
+        false => Err(ParseError::unparsed(input, rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{
+        bytes::complete::tag, character::complete::line_ending, multi::separated_list1,
+        sequence::terminated,
+    };
+
+    use super::*;
+
+    #[test]
+    fn number_fails_when_out_of_range() {
+        assert_eq!(number::<_, u8>("255 rest"), Ok((" rest", 255)));
+        assert!(number::<_, u8>("256").is_err());
+        assert!(number::<_, u64>("-1").is_err());
+        assert_eq!(signed_number::<_, i32>("-12,"), Ok((",", -12)));
+        assert_eq!(signed_number::<_, i32>("+7"), Ok(("", 7)));
+        assert!(signed_number::<_, i64>("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn numbered_header_tolerates_missing_spaces() {
+        let mut header = numbered_header::<u32, _>(tag("Game"));
+        assert_eq!(header("Game 12: 3 red"), Ok(("3 red", 12)));
+        assert_eq!(header("Game12 :3 red"), Ok(("3 red", 12)));
+        assert!(header("Game 12 3 red").is_err());
+    }
+
+    #[test]
+    fn blank_lines_need_an_empty_line() {
+        assert_eq!(blank_lines("\n\nnext"), Ok(("next", ())));
+        assert_eq!(blank_lines("\r\n \t\r\nnext"), Ok(("next", ())));
+        assert!(blank_lines("\nnext").is_err());
+    }
+
+    #[test]
+    fn sections_split_on_blank_lines() {
+        let lines = separated_list1(line_ending, number_list::<u32>);
+        let parsed = parse_all("1 2\n3\n\n4\r\n\r\n5 6\n", sections(lines));
+        assert_eq!(
+            parsed,
+            Ok(vec![
+                vec![vec![1, 2], vec![3]],
+                vec![vec![4]],
+                vec![vec![5, 6]]
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_all_accepts_trailing_whitespace() {
+        assert_eq!(
+            parse_all("1 2 3 \n\n", number_list::<u32>),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(parse_all("1 2\r\n", number_list::<u32>), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn parse_all_reports_trailing_garbage() {
+        let err = parse_all("1 2\n  x y", number_list::<u32>).unwrap_err();
+        assert_eq!((err.line, err.column, err.found), (2, 3, Some('x')));
+        assert!(err.unparsed);
+        assert_eq!(err.to_string(), "line 2, column 3: unparsed input \"x y\"");
+    }
+
+    #[test]
+    fn errors_on_later_lines_are_located() {
+        let line = |i| terminated(number::<_, u32>, tag(";"))(i);
+
+        let err = parse_all("1;\r\n2;\r\n3,4\r\n", separated_list1(line_ending, line)).unwrap_err();
+        assert_eq!((err.line, err.column, err.found), (3, 1, Some('3')));
+        assert!(err.unparsed);
+
+        let err = parse_all("1;\r\n2,4\r\n", tuple((line, line_ending, line))).unwrap_err();
+        assert_eq!((err.line, err.column, err.found), (2, 2, Some(',')));
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 2: unexpected ',' in \",4\""
+        );
+
+        let err = parse_all("1;\r\n2", tuple((line, line_ending, line))).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: unexpected end of input");
+
+        let err = parse_all("1;\r\n", tuple((line, line_ending, line))).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 1: unexpected end of input");
+    }
+
+    #[test]
+    fn at_accepts_rest_from_elsewhere() {
+        let input = "ab\ncd";
+        let copy = String::from("d");
+        assert_eq!(ParseError::at(input, &copy).column, 2);
+        assert_eq!(ParseError::at(input, "too long to be a suffix").found, None);
+        assert_eq!(ParseError::at("é", "x").found, None);
+    }
+}